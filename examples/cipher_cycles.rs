@@ -0,0 +1,54 @@
+//! Compare the cost in zkVM cycles of each guest cipher, by executing the sha256-sudoku
+//! program without proving.
+use risc0_zkvm::{default_executor, ExecutorEnv};
+use zkcp::cipher::{
+    cipher_name, Aes128CtrCipher, ChaCha20Cipher, GuestCipher, Sha256KeystreamCipher,
+};
+use zkcp::program::Program;
+use zkcp::proofs::sha256_sudoku::Sha256SudokuCipherProgram;
+
+/// A valid sudoku solution, built by shifting each row of `1..=9`. Every cipher encrypts
+/// the same compressed board, so the solution itself does not affect the comparison.
+fn solution() -> [u8; 81] {
+    let mut board = [0u8; 81];
+    for (i, cell) in board.iter_mut().enumerate() {
+        let (row, col) = (i / 9, i % 9);
+        *cell = ((row * 3 + row / 3 + col) % 9 + 1) as u8;
+    }
+    board
+}
+
+fn user_cycles<C: GuestCipher>() -> u64 {
+    let mut input = Vec::new();
+    input.extend_from_slice(&[3u8; 32]); // preimage
+    input.extend_from_slice(&[0u8; 12]); // cipher nonce
+    input.extend_from_slice(&[1u8; 81]); // mask
+    input.extend_from_slice(&solution());
+    input.push(C::ID);
+
+    let env = ExecutorEnv::builder()
+        .stdin(input.as_slice())
+        .build()
+        .unwrap();
+    let session_info = default_executor()
+        .execute(env, Sha256SudokuCipherProgram::<C>::elf())
+        .unwrap();
+    session_info
+        .segments
+        .iter()
+        .map(|segment| segment.cycles as u64)
+        .sum()
+}
+
+fn main() {
+    for (id, cycles) in [
+        (ChaCha20Cipher::ID, user_cycles::<ChaCha20Cipher>()),
+        (Aes128CtrCipher::ID, user_cycles::<Aes128CtrCipher>()),
+        (
+            Sha256KeystreamCipher::ID,
+            user_cycles::<Sha256KeystreamCipher>(),
+        ),
+    ] {
+        println!("{:>16}: {cycles} cycles", cipher_name(id).unwrap());
+    }
+}
@@ -1,6 +1,6 @@
 use std::time::Instant;
 use zkcp::proofs::dlog_secp256k1_sudoku::Secp256k1DlogSudokuProof;
-use zkcp::sudoku::is_valid_sudoku_solution;
+use zkcp::sudoku::{is_valid_sudoku_solution, Sudoku};
 
 fn main() {
     let secret_key = secp::Scalar::reduce_from(&[3u8; 32]);
@@ -52,8 +52,8 @@ fn main() {
 
     println!("proof is valid; discrete log of {:x}", proof.public_key);
     println!(
-        "...is also the decryption key to a solution for the sudoku puzzle:\n{:?}",
-        proof.puzzle()
+        "...is also the decryption key to a solution for the sudoku puzzle:\n{:#}",
+        Sudoku(proof.puzzle())
     );
 
     let proof_serialized = borsh::to_vec(&proof).unwrap();
@@ -1,9 +1,10 @@
 use std::time::Instant;
 use zkcp::proofs::sha256_sudoku::Sha256SudokuProof;
-use zkcp::sudoku::is_valid_sudoku_solution;
+use zkcp::secret::Secret;
+use zkcp::sudoku::{is_valid_sudoku_solution, Sudoku};
 
 fn main() {
-    let preimage = [3u8; 32];
+    let preimage = Secret::new([3u8; 32]);
 
     let solution = [
         6, 1, 4, /**/ 3, 8, 9, /**/ 2, 5, 7, //
@@ -35,7 +36,7 @@ fn main() {
     let prove_start_time = Instant::now();
     println!("proving execution...");
 
-    let proof = Sha256SudokuProof::new(preimage, &solution, &mask).unwrap();
+    let proof = Sha256SudokuProof::new(&preimage, &solution, &mask).unwrap();
 
     assert_eq!(
         proof.puzzle(),
@@ -72,13 +73,13 @@ fn main() {
 
     println!("proof is valid; preimage of {}", hex::encode(proof.hash()));
     println!(
-        "...is also the decryption key to a solution for the sudoku puzzle:\n{:?}",
-        proof.puzzle()
+        "...is also the decryption key to a solution for the sudoku puzzle:\n{:#}",
+        Sudoku(proof.puzzle())
     );
 
     let proof_serialized = borsh::to_vec(&proof).unwrap();
     println!("Receipt is {} bytes long", proof_serialized.len());
 
-    let solution = proof.decrypt_solution(preimage).unwrap();
+    let solution = proof.decrypt_solution(&preimage).unwrap();
     assert!(is_valid_sudoku_solution(&solution));
 }
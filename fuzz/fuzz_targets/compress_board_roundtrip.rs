@@ -0,0 +1,14 @@
+#![no_main]
+
+use common::sudoku;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|cells: sudoku::SudokuBoard| {
+    let mut board = cells;
+    for cell in board.iter_mut() {
+        *cell %= 10;
+    }
+
+    let compact_board = sudoku::compress_board(&board);
+    assert_eq!(sudoku::decompress_board(&compact_board), Ok(board));
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use common::sudoku;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|compact_board: sudoku::CompactSudokuBoard| {
+    // Any board which decompresses must compress back to exactly the same bytes.
+    if let Ok(board) = sudoku::decompress_board(&compact_board) {
+        assert!(board.iter().all(|&cell| cell <= 9));
+        assert_eq!(sudoku::compress_board(&board), compact_board);
+    }
+});
@@ -0,0 +1,24 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zkcp::proofs::dlog_secp256k1_sudoku::Secp256k1DlogSudokuProof;
+use zkcp::secp::Scalar;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(proof) = Secp256k1DlogSudokuProof::from_bytes(data) else {
+        return;
+    };
+
+    // Any decoded proof must survive a round trip through the encoder.
+    let encoded = proof.to_vec().unwrap();
+    let decoded = Secp256k1DlogSudokuProof::from_bytes(&encoded).unwrap();
+    assert_eq!(decoded.to_vec().unwrap(), encoded);
+
+    // None of the accessors may panic on an untrusted proof.
+    let _ = proof.challenge();
+    let _ = proof.signature();
+    let _ = proof.appendix();
+    let _ = proof.puzzle();
+    let _ = proof.decrypt_solution(Scalar::one());
+    let _ = proof.verify();
+});
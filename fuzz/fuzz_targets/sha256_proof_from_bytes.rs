@@ -0,0 +1,22 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zkcp::proofs::sha256_sudoku::Sha256SudokuProof;
+use zkcp::secret::Secret;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(proof) = Sha256SudokuProof::from_bytes(data) else {
+        return;
+    };
+
+    // Any decoded proof must survive a round trip through the encoder.
+    let encoded = proof.to_vec().unwrap();
+    let decoded = Sha256SudokuProof::from_bytes(&encoded).unwrap();
+    assert_eq!(decoded.to_vec().unwrap(), encoded);
+
+    // None of the accessors may panic on an untrusted proof.
+    let _ = proof.hash();
+    let _ = proof.puzzle();
+    let _ = proof.decrypt_solution(&Secret::new([0u8; 32]));
+    let _ = proof.verify();
+});
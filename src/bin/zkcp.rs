@@ -0,0 +1,269 @@
+//! Command-line tool for proving, verifying, inspecting and decrypting zkcp proofs.
+//!
+//! Proofs are read and written in the [envelope](zkcp::envelope) format. Sudoku boards
+//! are read from text files in any format accepted by [`Sudoku`].
+
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use anyhow::{bail, Context};
+use clap::{Args, Parser, Subcommand};
+use risc0_zkvm::sha::Digest;
+use zeroize::Zeroizing;
+use zkcp::envelope::AnyProof;
+use zkcp::image_ids;
+use zkcp::proofs::dlog_secp256k1_sha256::Secp256k1DlogSha256Proof;
+use zkcp::proofs::dlog_secp256k1_sudoku::Secp256k1DlogSudokuV2Proof;
+use zkcp::secp::Scalar;
+use zkcp::secret::Secret;
+use zkcp::sudoku::{Sudoku, SudokuBoard};
+
+#[derive(Parser)]
+#[command(
+    name = "zkcp",
+    version,
+    about = "Prove, verify and decrypt zkcp proofs"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a proof. This takes several minutes.
+    Prove {
+        #[command(subcommand)]
+        program: ProveCommand,
+    },
+    /// Verify a proof, exiting with a non-zero status if it is invalid.
+    Verify { proof: PathBuf },
+    /// Print the public contents of a proof, without verifying it.
+    Inspect { proof: PathBuf },
+    /// Decrypt the solution in a sudoku proof. For SHA256 proofs, the key is the preimage.
+    Decrypt {
+        proof: PathBuf,
+        #[command(flatten)]
+        key: KeyArgs,
+    },
+    /// Print the image ID of every guest program in this build, for publishing with a
+    /// release.
+    ImageIds,
+}
+
+#[derive(Subcommand)]
+enum ProveCommand {
+    /// Prove that a secret key decrypts the solution to a sudoku puzzle.
+    Sudoku {
+        #[command(flatten)]
+        key: KeyArgs,
+        /// File containing the solved board.
+        #[arg(long)]
+        solution: PathBuf,
+        /// File containing the mask: 1 for cells revealed in the puzzle, 0 otherwise.
+        #[arg(long)]
+        mask: PathBuf,
+        /// File to write the proof to.
+        #[arg(long, short)]
+        out: PathBuf,
+    },
+    /// Prove that a secret key is the preimage of its SHA256 hash.
+    DlogSha256 {
+        #[command(flatten)]
+        key: KeyArgs,
+        /// File to write the proof to.
+        #[arg(long, short)]
+        out: PathBuf,
+    },
+}
+
+/// Where to read a 32-byte secret key from, in hex. The key is read from stdin unless
+/// `--key` or `--key-file` is given.
+#[derive(Args)]
+struct KeyArgs {
+    /// The secret key. Other users of the system may be able to see command-line
+    /// arguments, so prefer --key-file or stdin.
+    #[arg(long, conflicts_with = "key_file")]
+    key: Option<String>,
+    /// File containing the secret key.
+    #[arg(long)]
+    key_file: Option<PathBuf>,
+}
+
+fn read_key_bytes(args: KeyArgs) -> Result<Zeroizing<[u8; 32]>, anyhow::Error> {
+    let key_hex = match (args.key, args.key_file) {
+        (Some(key), _) => Zeroizing::new(key),
+        (None, Some(path)) => Zeroizing::new(
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?,
+        ),
+        (None, None) => {
+            let mut key = Zeroizing::new(String::new());
+            io::stdin()
+                .read_to_string(&mut key)
+                .context("reading secret key from stdin")?;
+            key
+        }
+    };
+
+    let key_vec = Zeroizing::new(hex::decode(key_hex.trim()).context("secret key is not hex")?);
+    let mut key_bytes = Zeroizing::new([0u8; 32]);
+    if key_vec.len() != key_bytes.len() {
+        bail!("secret key must be 32 bytes; got {}", key_vec.len());
+    }
+    key_bytes.copy_from_slice(&key_vec);
+    Ok(key_bytes)
+}
+
+fn read_secret_key(args: KeyArgs) -> Result<Scalar, anyhow::Error> {
+    let key_bytes = read_key_bytes(args)?;
+    Scalar::try_from(key_bytes.as_slice()).context("secret key is not a valid secp256k1 scalar")
+}
+
+fn print_puzzles(puzzles: &[SudokuBoard]) {
+    for (i, puzzle) in puzzles.iter().enumerate() {
+        println!("puzzle {i}:\n{:#}", Sudoku(*puzzle));
+    }
+}
+
+fn print_solutions(solutions: &[SudokuBoard]) {
+    for (i, solution) in solutions.iter().enumerate() {
+        println!("solution {i}:\n{:#}", Sudoku(*solution));
+    }
+}
+
+fn read_board(path: &Path) -> Result<SudokuBoard, anyhow::Error> {
+    let text = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let sudoku: Sudoku = text
+        .parse()
+        .with_context(|| format!("parsing {}", path.display()))?;
+    Ok(sudoku.0)
+}
+
+fn read_proof(path: &Path) -> Result<AnyProof, anyhow::Error> {
+    let bytes = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    AnyProof::from_envelope(&bytes).with_context(|| format!("decoding {}", path.display()))
+}
+
+fn write_proof(path: &Path, proof: AnyProof) -> Result<(), anyhow::Error> {
+    fs::write(path, proof.to_envelope()?).with_context(|| format!("writing {}", path.display()))
+}
+
+fn run(cli: Cli) -> Result<(), anyhow::Error> {
+    match cli.command {
+        Command::Prove { program } => match program {
+            ProveCommand::Sudoku {
+                key,
+                solution,
+                mask,
+                out,
+            } => {
+                let secret_key = read_secret_key(key)?;
+                let solution = Zeroizing::new(read_board(&solution)?);
+                let mask = read_board(&mask)?;
+                let proof = Secp256k1DlogSudokuV2Proof::new(secret_key, &solution, &mask)?;
+                write_proof(&out, proof.into())?;
+            }
+            ProveCommand::DlogSha256 { key, out } => {
+                let proof = Secp256k1DlogSha256Proof::new(read_secret_key(key)?)?;
+                write_proof(&out, proof.into())?;
+            }
+        },
+
+        Command::Verify { proof } => {
+            read_proof(&proof)?.verify()?;
+            println!("proof is valid");
+        }
+
+        Command::Inspect { proof } => {
+            let proof = read_proof(&proof)?;
+            println!("program:      {}", proof.program_name());
+            println!("image ID:     {}", proof.image_id());
+            println!("claim digest: {}", proof.claim_digest());
+            match &proof {
+                AnyProof::Secp256k1DlogSha256(proof) => {
+                    println!("public key:   {:x}", proof.public_key);
+                    println!("public nonce: {:x}", proof.public_nonce);
+                    println!("hash:         {}", hex::encode(proof.hash()));
+                }
+                AnyProof::Secp256k1DlogSudoku(proof) => {
+                    println!("public key:   {:x}", proof.public_key());
+                    println!("public nonce: {:x}", proof.public_nonce());
+                    println!("puzzle:\n{:#}", Sudoku(proof.puzzle()));
+                }
+                AnyProof::Secp256k1DlogSudokuBatch(proof) => {
+                    println!("public key:   {:x}", proof.public_key());
+                    println!("public nonce: {:x}", proof.public_nonce());
+                    print_puzzles(&proof.puzzles());
+                }
+                AnyProof::Secp256k1DlogSudokuMultiKey(proof) => {
+                    let keys = proof.public_keys().iter().zip(proof.public_nonces());
+                    for (i, (public_key, public_nonce)) in keys.enumerate() {
+                        println!("public key {i}:   {public_key:x}");
+                        println!("public nonce {i}: {public_nonce:x}");
+                    }
+                    print_puzzles(&proof.puzzles());
+                }
+                AnyProof::Sha256Sudoku(proof) => {
+                    println!("hash:         {}", hex::encode(proof.hash()));
+                    println!("puzzle:\n{:#}", Sudoku(proof.puzzle()));
+                }
+            }
+        }
+
+        Command::Decrypt { proof, key } => match read_proof(&proof)? {
+            AnyProof::Secp256k1DlogSudoku(proof) => {
+                let solution = Zeroizing::new(proof.decrypt_solution(read_secret_key(key)?)?);
+                println!("{:#}", Sudoku(*solution));
+            }
+            AnyProof::Secp256k1DlogSudokuBatch(proof) => {
+                let solutions = Zeroizing::new(proof.decrypt_solutions(read_secret_key(key)?)?);
+                print_solutions(&solutions);
+            }
+            AnyProof::Secp256k1DlogSudokuMultiKey(proof) => {
+                // Only the items of the matching public key can be decrypted.
+                let secret_key = read_secret_key(key)?;
+                let public_key = secret_key.base_point_mul();
+                let items: Vec<usize> = (0..proof.len())
+                    .filter(|&i| proof.public_keys()[i] == public_key)
+                    .collect();
+                if items.is_empty() {
+                    bail!("secret key does not match any public key in the proof");
+                }
+                for i in items {
+                    let solution = Zeroizing::new(proof.decrypt_item(i, secret_key)?);
+                    println!("solution {i}:\n{:#}", Sudoku(*solution));
+                }
+            }
+            AnyProof::Sha256Sudoku(proof) => {
+                let preimage = Secret::new(*read_key_bytes(key)?);
+                let solution = Zeroizing::new(proof.decrypt_solution(&preimage)?);
+                println!("{:#}", Sudoku(*solution));
+            }
+            proof => bail!("{} proofs have nothing to decrypt", proof.program_name()),
+        },
+
+        Command::ImageIds => {
+            for known in image_ids::CURRENT_IMAGE_IDS {
+                println!(
+                    "{:<32} {} {}",
+                    known.program,
+                    known.release,
+                    Digest::from(known.image_id)
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            ExitCode::FAILURE
+        }
+    }
+}
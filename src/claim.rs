@@ -0,0 +1,416 @@
+//! Detached proof claims, and attestations by trusted verifiers.
+//!
+//! A [`ProofClaim`] is everything a proof asserts, without the zk-STARK seal: the image ID
+//! of the program, the journal it output, and for secp256k1 dlog proofs, the public key and
+//! public nonce which the journal's Schnorr signature is bound to. A claim is a few hundred
+//! bytes, where a receipt is several hundred kilobytes, so it is a convenient way to hand the
+//! public outputs of a proof to parties who don't need the proof itself.
+//!
+//! A claim proves nothing on its own. Either it is checked against the proof's [`Receipt`]
+//! later with [`ProofClaim::verify_receipt`], or a verifier service which the recipient
+//! trusts checks the receipt once, and vouches for the claim with a [`SignedAttestation`].
+
+use std::io;
+
+use anyhow::bail;
+use borsh::{BorshDeserialize, BorshSerialize};
+use risc0_zkvm::sha::rust_crypto::{Digest as _, Sha256};
+use risc0_zkvm::sha::{Digest, Digestible as _};
+use risc0_zkvm::{Receipt, ReceiptClaim};
+use secp::{MaybeScalar, Point, Scalar, G};
+use zeroize::Zeroizing;
+
+use crate::error::VerifyError;
+use crate::hashes::{self, PROTOCOL_VERSION};
+use crate::proofs::dlog_secp256k1_generic::compute_challenge;
+use crate::receipt::{self, VerifyOpts};
+
+/// The public key and public nonce which the Schnorr signature in the journal of a
+/// secp256k1 dlog proof is bound to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DlogKeys {
+    pub public_key: Point,
+    pub public_nonce: Point,
+}
+
+/// The public outputs of a proof, detached from its receipt.
+///
+/// Obtain a claim from a proof with its `claim` method.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ProofClaim {
+    /// The image ID of the program which produced the proof.
+    pub image_id: [u32; 8],
+    /// The journal output by the program.
+    pub journal: Vec<u8>,
+    /// The keys the journal is bound to, if the proof is a secp256k1 dlog proof.
+    pub dlog_keys: Option<DlogKeys>,
+}
+
+impl BorshSerialize for ProofClaim {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        BorshSerialize::serialize(&PROTOCOL_VERSION, writer)?;
+        BorshSerialize::serialize(&self.image_id, writer)?;
+        BorshSerialize::serialize(&self.journal, writer)?;
+        let dlog_keys = self
+            .dlog_keys
+            .map(|keys| (keys.public_key.serialize(), keys.public_nonce.serialize()));
+        BorshSerialize::serialize(&dlog_keys, writer)?;
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for ProofClaim {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        check_version(BorshDeserialize::deserialize_reader(reader)?)?;
+
+        let image_id: [u32; 8] = BorshDeserialize::deserialize_reader(reader)?;
+        let journal: Vec<u8> = BorshDeserialize::deserialize_reader(reader)?;
+        let dlog_keys: Option<([u8; 33], [u8; 33])> = BorshDeserialize::deserialize_reader(reader)?;
+        let dlog_keys = match dlog_keys {
+            Some((public_key, public_nonce)) => Some(DlogKeys {
+                public_key: read_point(public_key)?,
+                public_nonce: read_point(public_nonce)?,
+            }),
+            None => None,
+        };
+
+        Ok(ProofClaim {
+            image_id,
+            journal,
+            dlog_keys,
+        })
+    }
+}
+
+impl ProofClaim {
+    /// Return the digest of the claim, exactly as the `claim_digest` method of the proof it
+    /// was derived from does.
+    pub fn digest(&self) -> Digest {
+        ReceiptClaim::ok(self.image_id, self.journal.clone()).digest()
+    }
+
+    /// Verify that `receipt` proves this claim. Returns `Ok` if the receipt's journal is the
+    /// journal of the claim, the Schnorr signature in the journal is valid under the claim's
+    /// [`DlogKeys`] if any, and the receipt is a valid proof for the claim's image ID.
+    ///
+    /// Fake receipts produced in dev mode are always rejected.
+    pub fn verify_receipt(&self, receipt: &Receipt) -> Result<(), anyhow::Error> {
+        self.verify_receipt_with_opts(receipt, &VerifyOpts::default())
+    }
+
+    /// Verify the claim against `receipt` as with [`verify_receipt`][Self::verify_receipt],
+    /// but with custom options.
+    pub fn verify_receipt_with_opts(
+        &self,
+        receipt: &Receipt,
+        opts: &VerifyOpts,
+    ) -> Result<(), anyhow::Error> {
+        if receipt.journal.bytes != self.journal {
+            bail!(VerifyError::ClaimJournalMismatch);
+        }
+        self.verify_signature()?;
+        receipt::verify_image_id(receipt, self.image_id, opts)
+    }
+
+    /// Feed everything the claim asserts into `hasher`: its [digest][Self::digest], and its
+    /// [`DlogKeys`] if any, which the digest does not cover.
+    fn commit(&self, hasher: Sha256) -> Sha256 {
+        let hasher = hasher.chain_update(self.digest());
+        match self.dlog_keys {
+            Some(keys) => hasher
+                .chain_update([1u8])
+                .chain_update(keys.public_key.serialize())
+                .chain_update(keys.public_nonce.serialize()),
+            None => hasher.chain_update([0u8]),
+        }
+    }
+
+    /// If the claim has [`DlogKeys`], check the Schnorr challenge and signature at the start
+    /// of the journal, exactly as `Secp256k1DlogProof::verify` does.
+    fn verify_signature(&self) -> Result<(), anyhow::Error> {
+        let Some(keys) = self.dlog_keys else {
+            return Ok(());
+        };
+        if self.journal.len() < 64 {
+            bail!(VerifyError::JournalLength {
+                actual: self.journal.len(),
+                expected: 64,
+            });
+        }
+
+        let challenge = compute_challenge(self.image_id, keys.public_nonce, keys.public_key);
+        let journal_challenge =
+            MaybeScalar::try_from(&self.journal[..32]).map_err(|_| VerifyError::InvalidScalar)?;
+        if challenge != journal_challenge {
+            bail!(VerifyError::ChallengeMismatch);
+        }
+
+        let s =
+            MaybeScalar::try_from(&self.journal[32..64]).map_err(|_| VerifyError::InvalidScalar)?;
+        if s * G != keys.public_nonce + keys.public_key * challenge {
+            bail!(VerifyError::InvalidSignature);
+        }
+        Ok(())
+    }
+
+    /// Serialize the claim to bytes, prefixed with the [`PROTOCOL_VERSION`] byte.
+    ///
+    /// We use [`borsh`](https://github.com/near/borsh-rs) for binary serialization.
+    pub fn to_vec(&self) -> io::Result<Vec<u8>> {
+        borsh::to_vec(self)
+    }
+
+    /// Deserialize a claim from a vector of bytes.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        borsh::from_slice(bytes)
+    }
+}
+
+/// A statement by a verifier that it has checked a receipt proving `claim`, signed with a
+/// Schnorr signature under the verifier's secp256k1 key.
+///
+/// The signature commits to the [claim digest][ProofClaim::digest] and to the claim's
+/// [`DlogKeys`], using the tagged hashes in [`crate::hashes`], so an attestation can never be confused with a proof's own Schnorr
+/// signature, nor reused for another claim or protocol version.
+///
+/// An attestation is only as trustworthy as the verifier which signed it.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SignedAttestation {
+    pub claim: ProofClaim,
+    pub verifier_key: Point,
+    pub nonce: Point,
+    pub signature: MaybeScalar,
+}
+
+impl BorshSerialize for SignedAttestation {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        BorshSerialize::serialize(&PROTOCOL_VERSION, writer)?;
+        BorshSerialize::serialize(&self.claim, writer)?;
+        BorshSerialize::serialize(&self.verifier_key.serialize(), writer)?;
+        BorshSerialize::serialize(&self.nonce.serialize(), writer)?;
+        BorshSerialize::serialize(&self.signature.serialize(), writer)?;
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for SignedAttestation {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        check_version(BorshDeserialize::deserialize_reader(reader)?)?;
+
+        let claim: ProofClaim = BorshDeserialize::deserialize_reader(reader)?;
+        let verifier_key = read_point(BorshDeserialize::deserialize_reader(reader)?)?;
+        let nonce = read_point(BorshDeserialize::deserialize_reader(reader)?)?;
+        let signature_bytes: [u8; 32] = BorshDeserialize::deserialize_reader(reader)?;
+        let signature = MaybeScalar::try_from(signature_bytes.as_slice())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        Ok(SignedAttestation {
+            claim,
+            verifier_key,
+            nonce,
+            signature,
+        })
+    }
+}
+
+impl SignedAttestation {
+    /// Sign an attestation for `claim` with the verifier's `signing_key`.
+    ///
+    /// This does not check the claim. The verifier must first verify the proof the claim
+    /// was derived from, or verify the claim against its receipt with
+    /// [`ProofClaim::verify_receipt`].
+    pub fn sign(claim: ProofClaim, signing_key: Scalar) -> Self {
+        let signing_key_bytes = Zeroizing::new(signing_key.serialize());
+        let nonce_hasher = hashes::tagged_hasher(hashes::ATTESTATION_NONCE_TAG)
+            .chain_update(signing_key_bytes.as_slice());
+        let secret_nonce = Scalar::reduce_from(&claim.commit(nonce_hasher).finalize().into());
+
+        let verifier_key = signing_key * G;
+        let nonce = secret_nonce * G;
+        let challenge = attestation_challenge(nonce, verifier_key, &claim);
+        let signature = secret_nonce + challenge * signing_key;
+
+        SignedAttestation {
+            claim,
+            verifier_key,
+            nonce,
+            signature,
+        }
+    }
+
+    /// Verify that the attestation was signed by the verifier with the public key
+    /// `trusted_key`. Returns `Ok` if the verifier key matches, the signature is valid, and
+    /// the Schnorr signature in the claim's journal is valid under its [`DlogKeys`] if any.
+    ///
+    /// This does not verify any receipt: the whole point of an attestation is that the
+    /// recipient trusts the verifier to have done so.
+    pub fn verify(&self, trusted_key: Point) -> Result<(), anyhow::Error> {
+        if self.verifier_key != trusted_key {
+            bail!(VerifyError::UntrustedVerifier);
+        }
+
+        let challenge = attestation_challenge(self.nonce, self.verifier_key, &self.claim);
+        if self.signature * G != self.nonce + self.verifier_key * challenge {
+            bail!(VerifyError::InvalidSignature);
+        }
+        self.claim.verify_signature()
+    }
+
+    /// Serialize the attestation to bytes, prefixed with the [`PROTOCOL_VERSION`] byte.
+    ///
+    /// We use [`borsh`](https://github.com/near/borsh-rs) for binary serialization.
+    pub fn to_vec(&self) -> io::Result<Vec<u8>> {
+        borsh::to_vec(self)
+    }
+
+    /// Deserialize an attestation from a vector of bytes.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        borsh::from_slice(bytes)
+    }
+}
+
+fn attestation_challenge(nonce: Point, verifier_key: Point, claim: &ProofClaim) -> MaybeScalar {
+    let hasher = hashes::tagged_hasher(hashes::ATTESTATION_TAG)
+        .chain_update(nonce.serialize())
+        .chain_update(verifier_key.serialize());
+    MaybeScalar::reduce_from(&claim.commit(hasher).finalize().into())
+}
+
+fn check_version(version: u8) -> io::Result<()> {
+    if version != PROTOCOL_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported format version {version}; expected {PROTOCOL_VERSION}"),
+        ));
+    }
+    Ok(())
+}
+
+fn read_point(bytes: [u8; 33]) -> io::Result<Point> {
+    Point::try_from(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IMAGE_ID: [u32; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    /// Build a claim whose journal holds a valid Schnorr signature, as a dlog guest would.
+    fn dlog_claim() -> ProofClaim {
+        let secret_key = Scalar::reduce_from(&[3u8; 32]);
+        let secret_nonce = Scalar::reduce_from(&[4u8; 32]);
+        let keys = DlogKeys {
+            public_key: secret_key * G,
+            public_nonce: secret_nonce * G,
+        };
+        let challenge = compute_challenge(IMAGE_ID, keys.public_nonce, keys.public_key);
+        let s = secret_nonce + challenge * secret_key;
+
+        let mut journal = Vec::new();
+        journal.extend_from_slice(&challenge.serialize());
+        journal.extend_from_slice(&s.serialize());
+        journal.extend_from_slice(b"appendix");
+        ProofClaim {
+            image_id: IMAGE_ID,
+            journal,
+            dlog_keys: Some(keys),
+        }
+    }
+
+    #[test]
+    fn test_claim_signature() {
+        let claim = dlog_claim();
+        claim.verify_signature().unwrap();
+
+        let mut tampered = claim.clone();
+        tampered.image_id[0] ^= 1;
+        assert!(tampered.verify_signature().is_err());
+
+        let mut tampered = claim.clone();
+        tampered.journal[63] ^= 1;
+        assert!(tampered.verify_signature().is_err());
+
+        let mut short = claim.clone();
+        short.journal.truncate(40);
+        assert!(short.verify_signature().is_err());
+
+        // Claims without keys have no signature to check.
+        short.dlog_keys = None;
+        short.verify_signature().unwrap();
+    }
+
+    #[test]
+    fn test_attestation() {
+        let signing_key = Scalar::reduce_from(&[9u8; 32]);
+        let verifier_key = signing_key * G;
+        let claim = dlog_claim();
+
+        let attestation = SignedAttestation::sign(claim.clone(), signing_key);
+        attestation.verify(verifier_key).unwrap();
+        assert_eq!(attestation, SignedAttestation::sign(claim, signing_key));
+
+        let decoded = SignedAttestation::from_bytes(&attestation.to_vec().unwrap()).unwrap();
+        assert_eq!(decoded, attestation);
+        decoded.verify(verifier_key).unwrap();
+
+        let other_key = Scalar::reduce_from(&[10u8; 32]) * G;
+        let err = attestation.verify(other_key).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<VerifyError>(),
+            Some(VerifyError::UntrustedVerifier)
+        ));
+
+        let mut tampered = attestation.clone();
+        tampered.claim.journal[70] ^= 1;
+        let err = tampered.verify(verifier_key).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<VerifyError>(),
+            Some(VerifyError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_attestation_covers_dlog_keys() {
+        let signing_key = Scalar::reduce_from(&[9u8; 32]);
+        let verifier_key = signing_key * G;
+        let claim = dlog_claim();
+        let attestation = SignedAttestation::sign(claim.clone(), signing_key);
+
+        // Swapping in another key must break the attestation, even though the claim digest
+        // is unchanged.
+        let other_point = Scalar::reduce_from(&[11u8; 32]) * G;
+        let tampers: [fn(&mut DlogKeys, Point); 2] = [
+            |keys, point| keys.public_key = point,
+            |keys, point| keys.public_nonce = point,
+        ];
+        for tamper in tampers {
+            let mut tampered = attestation.clone();
+            tamper(tampered.claim.dlog_keys.as_mut().unwrap(), other_point);
+            assert_eq!(tampered.claim.digest(), claim.digest());
+            let err = tampered.verify(verifier_key).unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<VerifyError>(),
+                Some(VerifyError::InvalidSignature)
+            ));
+        }
+
+        let mut tampered = attestation.clone();
+        tampered.claim.dlog_keys = None;
+        assert!(tampered.verify(verifier_key).is_err());
+
+        // Claims which differ only in their keys get different attestation nonces.
+        let mut other_claim = claim.clone();
+        other_claim.dlog_keys.as_mut().unwrap().public_key = other_point;
+        let other = SignedAttestation::sign(other_claim, signing_key);
+        assert_ne!(other.nonce, attestation.nonce);
+
+        // An attestation over a claim whose journal signature is invalid does not verify,
+        // even if the verifier signed it.
+        let err = other.verify(verifier_key).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<VerifyError>(),
+            Some(VerifyError::ChallengeMismatch)
+        ));
+    }
+}
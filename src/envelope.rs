@@ -0,0 +1,489 @@
+//! A self-describing container for proofs, so that proofs can be stored in files and
+//! decoded without knowing in advance which program produced them.
+//!
+//! An envelope is the [`PROTOCOL_VERSION`] byte, followed by the image ID of the program
+//! which produced the proof, a parameter, and the proof as serialized by its own `to_vec`
+//! method. Some proof types share one guest program, so the parameter tells them apart:
+//! it is the cipher ID of proofs with a choice of cipher, the number of items in batch and
+//! multi-key proofs, and zero otherwise. Together, the image ID and parameter select how
+//! to decode the proof.
+//!
+//! Batch and multi-key proofs fix their number of items at the type level, so envelopes
+//! can only hold those with up to [`MAX_ITEMS`] items.
+
+use std::io;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use risc0_zkvm::sha::Digest;
+use secp::{Point, Scalar};
+
+use crate::claim::ProofClaim;
+use crate::hashes::PROTOCOL_VERSION;
+use crate::program::Program;
+use crate::proofs::dlog_secp256k1_sha256::{Secp256k1DlogSha256Program, Secp256k1DlogSha256Proof};
+use crate::proofs::dlog_secp256k1_sudoku::{
+    Secp256k1DlogSudokuAeadProgram, Secp256k1DlogSudokuAeadProof, Secp256k1DlogSudokuCipherProgram,
+    Secp256k1DlogSudokuCipherProof, Secp256k1DlogSudokuProgram, Secp256k1DlogSudokuProof,
+    Secp256k1DlogSudokuUniqueProgram, Secp256k1DlogSudokuUniqueProof, Secp256k1DlogSudokuV2Program,
+    Secp256k1DlogSudokuV2Proof,
+};
+use crate::proofs::dlog_secp256k1_sudoku_batch::{
+    Secp256k1DlogSudokuBatchProgram, Secp256k1DlogSudokuBatchProof,
+};
+use crate::proofs::dlog_secp256k1_sudoku_multikey::{
+    Secp256k1DlogSudokuMultiKeyProgram, Secp256k1DlogSudokuMultiKeyProof,
+};
+use crate::proofs::sha256_sudoku::{
+    Sha256SudokuAeadProgram, Sha256SudokuAeadProof, Sha256SudokuCipherProgram,
+    Sha256SudokuCipherProof, Sha256SudokuProgram, Sha256SudokuProof, Sha256SudokuV2Program,
+    Sha256SudokuV2Proof,
+};
+use crate::proofs::sha256_sudoku_composed::{
+    Sha256SudokuComposedProgram, Sha256SudokuComposedProof,
+};
+use crate::receipt::VerifyOpts;
+use crate::secret::Secret;
+use common::cipher::{Aes128CtrCipher, ChaCha20Cipher, GuestCipher, Sha256KeystreamCipher};
+use common::sudoku::SudokuBoard;
+
+/// The largest number of items in a batch or multi-key proof which can be stored in an
+/// envelope.
+pub const MAX_ITEMS: usize = 8;
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct Envelope {
+    image_id: [u32; 8],
+    param: u32,
+    proof: Vec<u8>,
+}
+
+/// Declare an enum over a family of proof types, each listed with its program, its program
+/// name and its envelope parameter, and forward each of the given methods to whichever proof
+/// the enum holds. The proof types must share the accessors which are forwarded.
+macro_rules! proof_family {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident in AnyProof::$outer:ident $variants:tt
+
+        $(
+            $(#[$method_meta:meta])*
+            pub fn $method:ident(&self $(, $arg:ident: $arg_ty:ty)*) -> $ret:ty =
+                |$proof:ident| $body:expr;
+        )*
+    ) => {
+        proof_family!(@enum $(#[$meta])* $name $outer $variants);
+
+        impl $name {
+            $(
+                proof_family!(
+                    @forward $variants
+                    $(#[$method_meta])*
+                    $method(&self $(, $arg: $arg_ty)*) -> $ret = |$proof| $body
+                );
+            )*
+        }
+    };
+
+    (
+        @enum $(#[$meta:meta])* $name:ident $outer:ident {
+            $($variant:ident($proof:ty) = $program:ty, $program_name:literal, $param:expr;)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug)]
+        pub enum $name {
+            $($variant($proof),)*
+        }
+
+        impl $name {
+            /// Returns the image ID of the program which produced the proof.
+            pub fn image_id(&self) -> Digest {
+                self.program_id().into()
+            }
+
+            /// Returns a short human-readable name for the program which produced the proof.
+            pub fn program_name(&self) -> &'static str {
+                match self {
+                    $($name::$variant(_) => $program_name,)*
+                }
+            }
+
+            fn program_id(&self) -> [u32; 8] {
+                match self {
+                    $($name::$variant(_) => <$program as Program>::id(),)*
+                }
+            }
+
+            fn param(&self) -> u32 {
+                match self {
+                    $($name::$variant(_) => $param,)*
+                }
+            }
+
+            /// Decode the proof in `envelope`, or return `None` if it is not of this family.
+            fn from_envelope(envelope: &Envelope) -> io::Result<Option<Self>> {
+                $(
+                    if envelope.image_id == <$program as Program>::id()
+                        && envelope.param == $param
+                    {
+                        let proof = <$proof>::from_bytes(&envelope.proof)?;
+                        return Ok(Some($name::$variant(proof)));
+                    }
+                )*
+                Ok(None)
+            }
+        }
+
+        $(
+            impl From<$proof> for AnyProof {
+                fn from(proof: $proof) -> Self {
+                    AnyProof::$outer($name::$variant(proof))
+                }
+            }
+        )*
+    };
+
+    (
+        @forward {
+            $($variant:ident($proof_ty:ty) = $program:ty, $program_name:literal, $param:expr;)*
+        }
+        $(#[$method_meta:meta])*
+        $method:ident(&self $(, $arg:ident: $arg_ty:ty)*) -> $ret:ty = |$proof:ident| $body:expr
+    ) => {
+        $(#[$method_meta])*
+        pub fn $method(&self $(, $arg: $arg_ty)*) -> $ret {
+            match self {
+                $(Self::$variant($proof) => $body,)*
+            }
+        }
+    };
+}
+
+proof_family! {
+    /// Any proof that the secret key of one secp256k1 public key decrypts a sudoku solution.
+    pub enum AnyDlogSudokuProof in AnyProof::Secp256k1DlogSudoku {
+        V1(Secp256k1DlogSudokuProof) =
+            Secp256k1DlogSudokuProgram, "dlog-secp256k1-sudoku", 0;
+        V2(Secp256k1DlogSudokuV2Proof) =
+            Secp256k1DlogSudokuV2Program, "dlog-secp256k1-sudoku-v2", 0;
+        Unique(Secp256k1DlogSudokuUniqueProof) =
+            Secp256k1DlogSudokuUniqueProgram, "dlog-secp256k1-sudoku-unique", 0;
+        Aead(Secp256k1DlogSudokuAeadProof) =
+            Secp256k1DlogSudokuAeadProgram, "dlog-secp256k1-sudoku-aead", 0;
+        ChaCha20(Secp256k1DlogSudokuCipherProof<ChaCha20Cipher>) =
+            Secp256k1DlogSudokuCipherProgram<ChaCha20Cipher>,
+            "dlog-secp256k1-sudoku-cipher", u32::from(ChaCha20Cipher::ID);
+        Aes128Ctr(Secp256k1DlogSudokuCipherProof<Aes128CtrCipher>) =
+            Secp256k1DlogSudokuCipherProgram<Aes128CtrCipher>,
+            "dlog-secp256k1-sudoku-cipher", u32::from(Aes128CtrCipher::ID);
+        Sha256Keystream(Secp256k1DlogSudokuCipherProof<Sha256KeystreamCipher>) =
+            Secp256k1DlogSudokuCipherProgram<Sha256KeystreamCipher>,
+            "dlog-secp256k1-sudoku-cipher", u32::from(Sha256KeystreamCipher::ID);
+    }
+
+    /// Verify the proof with custom options.
+    pub fn verify_with_opts(&self, opts: &VerifyOpts) -> Result<(), anyhow::Error> =
+        |proof| proof.verify_with_opts(opts);
+    pub fn to_vec(&self) -> io::Result<Vec<u8>> = |proof| proof.to_vec();
+    pub fn claim(&self) -> ProofClaim = |proof| proof.claim();
+    pub fn claim_digest(&self) -> Digest = |proof| proof.claim_digest();
+    pub fn public_key(&self) -> Point = |proof| proof.public_key;
+    pub fn public_nonce(&self) -> Point = |proof| proof.public_nonce;
+    pub fn puzzle(&self) -> SudokuBoard = |proof| proof.puzzle();
+    pub fn decrypt_solution(&self, secret_key: Scalar) -> Result<SudokuBoard, anyhow::Error> =
+        |proof| proof.decrypt_solution(secret_key);
+}
+
+proof_family! {
+    /// Any proof that the secret key of one secp256k1 public key decrypts the solutions to
+    /// a batch of sudoku puzzles, of up to [`MAX_ITEMS`] puzzles.
+    pub enum AnyBatchProof in AnyProof::Secp256k1DlogSudokuBatch {
+        Batch1(Secp256k1DlogSudokuBatchProof<1>) =
+            Secp256k1DlogSudokuBatchProgram<1>, "dlog-secp256k1-sudoku-batch", 1;
+        Batch2(Secp256k1DlogSudokuBatchProof<2>) =
+            Secp256k1DlogSudokuBatchProgram<2>, "dlog-secp256k1-sudoku-batch", 2;
+        Batch3(Secp256k1DlogSudokuBatchProof<3>) =
+            Secp256k1DlogSudokuBatchProgram<3>, "dlog-secp256k1-sudoku-batch", 3;
+        Batch4(Secp256k1DlogSudokuBatchProof<4>) =
+            Secp256k1DlogSudokuBatchProgram<4>, "dlog-secp256k1-sudoku-batch", 4;
+        Batch5(Secp256k1DlogSudokuBatchProof<5>) =
+            Secp256k1DlogSudokuBatchProgram<5>, "dlog-secp256k1-sudoku-batch", 5;
+        Batch6(Secp256k1DlogSudokuBatchProof<6>) =
+            Secp256k1DlogSudokuBatchProgram<6>, "dlog-secp256k1-sudoku-batch", 6;
+        Batch7(Secp256k1DlogSudokuBatchProof<7>) =
+            Secp256k1DlogSudokuBatchProgram<7>, "dlog-secp256k1-sudoku-batch", 7;
+        Batch8(Secp256k1DlogSudokuBatchProof<8>) =
+            Secp256k1DlogSudokuBatchProgram<8>, "dlog-secp256k1-sudoku-batch", 8;
+    }
+
+    /// Verify the proof with custom options.
+    pub fn verify_with_opts(&self, opts: &VerifyOpts) -> Result<(), anyhow::Error> =
+        |proof| proof.verify_with_opts(opts);
+    pub fn to_vec(&self) -> io::Result<Vec<u8>> = |proof| proof.to_vec();
+    pub fn claim(&self) -> ProofClaim = |proof| proof.claim();
+    pub fn claim_digest(&self) -> Digest = |proof| proof.claim_digest();
+    pub fn public_key(&self) -> Point = |proof| proof.public_key;
+    pub fn public_nonce(&self) -> Point = |proof| proof.public_nonce;
+    /// Returns the number of puzzles in the batch.
+    pub fn len(&self) -> usize = |proof| proof.len();
+    /// Returns true if the batch contains no puzzles.
+    pub fn is_empty(&self) -> bool = |proof| proof.is_empty();
+    pub fn puzzles(&self) -> Vec<SudokuBoard> = |proof| proof.puzzles();
+    pub fn decrypt_solutions(&self, secret_key: Scalar) -> Result<Vec<SudokuBoard>, anyhow::Error> =
+        |proof| proof.decrypt_solutions(secret_key);
+}
+
+proof_family! {
+    /// Any proof that the secret keys of several secp256k1 public keys each decrypt the
+    /// solution to their own sudoku puzzle, for up to [`MAX_ITEMS`] keys.
+    pub enum AnyMultiKeyProof in AnyProof::Secp256k1DlogSudokuMultiKey {
+        MultiKey1(Secp256k1DlogSudokuMultiKeyProof<1>) =
+            Secp256k1DlogSudokuMultiKeyProgram<1>, "dlog-secp256k1-sudoku-multikey", 1;
+        MultiKey2(Secp256k1DlogSudokuMultiKeyProof<2>) =
+            Secp256k1DlogSudokuMultiKeyProgram<2>, "dlog-secp256k1-sudoku-multikey", 2;
+        MultiKey3(Secp256k1DlogSudokuMultiKeyProof<3>) =
+            Secp256k1DlogSudokuMultiKeyProgram<3>, "dlog-secp256k1-sudoku-multikey", 3;
+        MultiKey4(Secp256k1DlogSudokuMultiKeyProof<4>) =
+            Secp256k1DlogSudokuMultiKeyProgram<4>, "dlog-secp256k1-sudoku-multikey", 4;
+        MultiKey5(Secp256k1DlogSudokuMultiKeyProof<5>) =
+            Secp256k1DlogSudokuMultiKeyProgram<5>, "dlog-secp256k1-sudoku-multikey", 5;
+        MultiKey6(Secp256k1DlogSudokuMultiKeyProof<6>) =
+            Secp256k1DlogSudokuMultiKeyProgram<6>, "dlog-secp256k1-sudoku-multikey", 6;
+        MultiKey7(Secp256k1DlogSudokuMultiKeyProof<7>) =
+            Secp256k1DlogSudokuMultiKeyProgram<7>, "dlog-secp256k1-sudoku-multikey", 7;
+        MultiKey8(Secp256k1DlogSudokuMultiKeyProof<8>) =
+            Secp256k1DlogSudokuMultiKeyProgram<8>, "dlog-secp256k1-sudoku-multikey", 8;
+    }
+
+    /// Verify the proof with custom options.
+    pub fn verify_with_opts(&self, opts: &VerifyOpts) -> Result<(), anyhow::Error> =
+        |proof| proof.verify_with_opts(opts);
+    pub fn to_vec(&self) -> io::Result<Vec<u8>> = |proof| proof.to_vec();
+    pub fn claim_digest(&self) -> Digest = |proof| proof.claim_digest();
+    pub fn public_keys(&self) -> &[Point] = |proof| proof.public_keys();
+    pub fn public_nonces(&self) -> &[Point] = |proof| proof.public_nonces();
+    /// Returns the number of keys, and so of puzzles.
+    pub fn len(&self) -> usize = |proof| proof.len();
+    /// Returns true if the proof contains no keys.
+    pub fn is_empty(&self) -> bool = |proof| proof.is_empty();
+    pub fn puzzles(&self) -> Vec<SudokuBoard> = |proof| proof.puzzles();
+    /// Decrypt the solution to the `i`th puzzle, using the secret key of the `i`th
+    /// public key.
+    pub fn decrypt_item(&self, i: usize, secret_key: Scalar) -> Result<SudokuBoard, anyhow::Error> =
+        |proof| proof.decrypt_item(i, secret_key);
+}
+
+proof_family! {
+    /// Any proof that the preimage of a SHA256 hash decrypts a sudoku solution.
+    pub enum AnySha256SudokuProof in AnyProof::Sha256Sudoku {
+        V1(Sha256SudokuProof) = Sha256SudokuProgram, "sha256-sudoku", 0;
+        V2(Sha256SudokuV2Proof) = Sha256SudokuV2Program, "sha256-sudoku-v2", 0;
+        Aead(Sha256SudokuAeadProof) = Sha256SudokuAeadProgram, "sha256-sudoku-aead", 0;
+        ChaCha20(Sha256SudokuCipherProof<ChaCha20Cipher>) =
+            Sha256SudokuCipherProgram<ChaCha20Cipher>,
+            "sha256-sudoku-cipher", u32::from(ChaCha20Cipher::ID);
+        Aes128Ctr(Sha256SudokuCipherProof<Aes128CtrCipher>) =
+            Sha256SudokuCipherProgram<Aes128CtrCipher>,
+            "sha256-sudoku-cipher", u32::from(Aes128CtrCipher::ID);
+        Sha256Keystream(Sha256SudokuCipherProof<Sha256KeystreamCipher>) =
+            Sha256SudokuCipherProgram<Sha256KeystreamCipher>,
+            "sha256-sudoku-cipher", u32::from(Sha256KeystreamCipher::ID);
+        Composed(Sha256SudokuComposedProof) =
+            Sha256SudokuComposedProgram, "sha256-sudoku-composed", 0;
+    }
+
+    /// Verify the proof with custom options. Composed proofs are verified without checking
+    /// which key their assumed receipt was for; see
+    /// [`verify_for_key`][Sha256SudokuComposedProof::verify_for_key].
+    pub fn verify_with_opts(&self, opts: &VerifyOpts) -> Result<(), anyhow::Error> =
+        |proof| proof.verify_with_opts(opts);
+    pub fn to_vec(&self) -> io::Result<Vec<u8>> = |proof| proof.to_vec();
+    pub fn claim(&self) -> ProofClaim = |proof| proof.claim();
+    pub fn claim_digest(&self) -> Digest = |proof| proof.claim_digest();
+    pub fn hash(&self) -> [u8; 32] = |proof| proof.hash();
+    pub fn puzzle(&self) -> SudokuBoard = |proof| proof.puzzle();
+    pub fn decrypt_solution(
+        &self,
+        preimage: &Secret<[u8; 32]>
+    ) -> Result<SudokuBoard, anyhow::Error> = |proof| proof.decrypt_solution(preimage);
+}
+
+/// Any proof which can be stored in an envelope.
+#[derive(Clone, Debug)]
+pub enum AnyProof {
+    Secp256k1DlogSha256(Secp256k1DlogSha256Proof),
+    Secp256k1DlogSudoku(AnyDlogSudokuProof),
+    Secp256k1DlogSudokuBatch(AnyBatchProof),
+    Secp256k1DlogSudokuMultiKey(AnyMultiKeyProof),
+    Sha256Sudoku(AnySha256SudokuProof),
+}
+
+impl AnyProof {
+    /// Returns the image ID of the program which produced the proof.
+    pub fn image_id(&self) -> Digest {
+        match self {
+            AnyProof::Secp256k1DlogSha256(_) => Secp256k1DlogSha256Program::id().into(),
+            AnyProof::Secp256k1DlogSudoku(proof) => proof.image_id(),
+            AnyProof::Secp256k1DlogSudokuBatch(proof) => proof.image_id(),
+            AnyProof::Secp256k1DlogSudokuMultiKey(proof) => proof.image_id(),
+            AnyProof::Sha256Sudoku(proof) => proof.image_id(),
+        }
+    }
+
+    /// Returns a short human-readable name for the program which produced the proof.
+    pub fn program_name(&self) -> &'static str {
+        match self {
+            AnyProof::Secp256k1DlogSha256(_) => "dlog-secp256k1-sha256",
+            AnyProof::Secp256k1DlogSudoku(proof) => proof.program_name(),
+            AnyProof::Secp256k1DlogSudokuBatch(proof) => proof.program_name(),
+            AnyProof::Secp256k1DlogSudokuMultiKey(proof) => proof.program_name(),
+            AnyProof::Sha256Sudoku(proof) => proof.program_name(),
+        }
+    }
+
+    /// Returns a digest of the proof's claim, as with the `claim_digest` method of each
+    /// proof type.
+    pub fn claim_digest(&self) -> Digest {
+        match self {
+            AnyProof::Secp256k1DlogSha256(proof) => proof.claim_digest(),
+            AnyProof::Secp256k1DlogSudoku(proof) => proof.claim_digest(),
+            AnyProof::Secp256k1DlogSudokuBatch(proof) => proof.claim_digest(),
+            AnyProof::Secp256k1DlogSudokuMultiKey(proof) => proof.claim_digest(),
+            AnyProof::Sha256Sudoku(proof) => proof.claim_digest(),
+        }
+    }
+
+    /// Verify the proof. Fake receipts produced in dev mode are always rejected.
+    pub fn verify(&self) -> Result<(), anyhow::Error> {
+        self.verify_with_opts(&VerifyOpts::default())
+    }
+
+    /// Verify the proof with custom options.
+    pub fn verify_with_opts(&self, opts: &VerifyOpts) -> Result<(), anyhow::Error> {
+        match self {
+            AnyProof::Secp256k1DlogSha256(proof) => proof.verify_with_opts(opts),
+            AnyProof::Secp256k1DlogSudoku(proof) => proof.verify_with_opts(opts),
+            AnyProof::Secp256k1DlogSudokuBatch(proof) => proof.verify_with_opts(opts),
+            AnyProof::Secp256k1DlogSudokuMultiKey(proof) => proof.verify_with_opts(opts),
+            AnyProof::Sha256Sudoku(proof) => proof.verify_with_opts(opts),
+        }
+    }
+
+    /// Serialize the proof into an envelope.
+    pub fn to_envelope(&self) -> io::Result<Vec<u8>> {
+        let envelope = match self {
+            AnyProof::Secp256k1DlogSha256(proof) => Envelope {
+                image_id: Secp256k1DlogSha256Program::id(),
+                param: 0,
+                proof: proof.to_vec()?,
+            },
+            AnyProof::Secp256k1DlogSudoku(proof) => Envelope {
+                image_id: proof.program_id(),
+                param: proof.param(),
+                proof: proof.to_vec()?,
+            },
+            AnyProof::Secp256k1DlogSudokuBatch(proof) => Envelope {
+                image_id: proof.program_id(),
+                param: proof.param(),
+                proof: proof.to_vec()?,
+            },
+            AnyProof::Secp256k1DlogSudokuMultiKey(proof) => Envelope {
+                image_id: proof.program_id(),
+                param: proof.param(),
+                proof: proof.to_vec()?,
+            },
+            AnyProof::Sha256Sudoku(proof) => Envelope {
+                image_id: proof.program_id(),
+                param: proof.param(),
+                proof: proof.to_vec()?,
+            },
+        };
+        borsh::to_vec(&(PROTOCOL_VERSION, envelope))
+    }
+
+    /// Deserialize a proof from an envelope. Fails if the envelope is from another protocol
+    /// version, or if the program which produced the proof is unknown.
+    pub fn from_envelope(bytes: &[u8]) -> io::Result<Self> {
+        let (version, envelope): (u8, Envelope) = borsh::from_slice(bytes)?;
+        if version != PROTOCOL_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported envelope version {version}; expected {PROTOCOL_VERSION}"),
+            ));
+        }
+
+        let proof = if envelope.image_id == Secp256k1DlogSha256Program::id() && envelope.param == 0
+        {
+            AnyProof::Secp256k1DlogSha256(Secp256k1DlogSha256Proof::from_bytes(&envelope.proof)?)
+        } else if let Some(proof) = AnyDlogSudokuProof::from_envelope(&envelope)? {
+            AnyProof::Secp256k1DlogSudoku(proof)
+        } else if let Some(proof) = AnyBatchProof::from_envelope(&envelope)? {
+            AnyProof::Secp256k1DlogSudokuBatch(proof)
+        } else if let Some(proof) = AnyMultiKeyProof::from_envelope(&envelope)? {
+            AnyProof::Secp256k1DlogSudokuMultiKey(proof)
+        } else if let Some(proof) = AnySha256SudokuProof::from_envelope(&envelope)? {
+            AnyProof::Sha256Sudoku(proof)
+        } else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "envelope contains a proof from unknown program {} with parameter {}",
+                    Digest::from(envelope.image_id),
+                    envelope.param,
+                ),
+            ));
+        };
+        Ok(proof)
+    }
+}
+
+impl From<Secp256k1DlogSha256Proof> for AnyProof {
+    fn from(proof: Secp256k1DlogSha256Proof) -> Self {
+        AnyProof::Secp256k1DlogSha256(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_rejects_unknown_program() {
+        let bytes = borsh::to_vec(&(
+            PROTOCOL_VERSION,
+            Envelope {
+                image_id: [7u32; 8],
+                param: 0,
+                proof: vec![],
+            },
+        ))
+        .unwrap();
+        let err = AnyProof::from_envelope(&bytes).unwrap_err();
+        assert!(err.to_string().contains("unknown program"));
+
+        // Batches too large for an envelope are as good as unknown.
+        let bytes = borsh::to_vec(&(
+            PROTOCOL_VERSION,
+            Envelope {
+                image_id: Secp256k1DlogSudokuBatchProgram::<1>::id(),
+                param: MAX_ITEMS as u32 + 1,
+                proof: vec![],
+            },
+        ))
+        .unwrap();
+        let err = AnyProof::from_envelope(&bytes).unwrap_err();
+        assert!(err.to_string().contains("unknown program"));
+
+        let bytes = borsh::to_vec(&(
+            PROTOCOL_VERSION + 1,
+            Envelope {
+                image_id: Secp256k1DlogSha256Program::id(),
+                param: 0,
+                proof: vec![],
+            },
+        ))
+        .unwrap();
+        let err = AnyProof::from_envelope(&bytes).unwrap_err();
+        assert!(err.to_string().contains("unsupported envelope version"));
+    }
+}
@@ -0,0 +1,130 @@
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::VerificationError;
+
+use crate::image_ids;
+use common::cipher;
+
+/// Errors which can occur when verifying a proof.
+///
+/// Proof verification methods return [`anyhow::Error`], which can be downcast to
+/// a `VerifyError` to find out exactly why a proof was rejected.
+#[derive(Debug)]
+pub enum VerifyError {
+    /// The journal is not the length output by the program.
+    JournalLength { actual: usize, expected: usize },
+    /// A scalar in the journal is not a valid secp256k1 scalar.
+    InvalidScalar,
+    /// The Schnorr challenge in the journal does not match the challenge computed from
+    /// the program ID, public nonce and public key.
+    ChallengeMismatch,
+    /// The Schnorr signature in the journal is not valid under the public key.
+    InvalidSignature,
+    /// The receipt's journal does not match the journal of the claim it was checked against.
+    ClaimJournalMismatch,
+    /// An attestation was signed by a verifier other than the one trusted.
+    UntrustedVerifier,
+    /// The receipt was produced by a program whose image ID the verifier does not accept,
+    /// usually a build of the program from another release of this crate.
+    ImageIdNotAllowed { image_id: [u32; 8] },
+    /// The journal commits to a different cipher than the one the proof type is for.
+    CipherMismatch { actual: u8, expected: u8 },
+    /// The receipt is fake, and fake receipts are not allowed.
+    FakeReceipt,
+    /// The claim of a fake receipt does not match the image ID and journal.
+    FakeReceiptClaimMismatch,
+    /// The zk-STARK receipt failed verification.
+    Receipt(VerificationError),
+}
+
+impl std::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            VerifyError::JournalLength { actual, expected } => {
+                write!(
+                    f,
+                    "journal is incorrect length {actual}; expected {expected}"
+                )
+            }
+            VerifyError::InvalidScalar => f.write_str("journal contains an invalid scalar"),
+            VerifyError::ChallengeMismatch => {
+                f.write_str("journal challenge does not match computed challenge")
+            }
+            VerifyError::InvalidSignature => f.write_str("schnorr signature is invalid"),
+            VerifyError::ClaimJournalMismatch => {
+                f.write_str("receipt journal does not match claim journal")
+            }
+            VerifyError::UntrustedVerifier => {
+                f.write_str("attestation was signed by an untrusted verifier")
+            }
+            VerifyError::ImageIdNotAllowed { image_id } => {
+                let version = match image_ids::lookup(*image_id) {
+                    Some(known) => format!("{known}"),
+                    None => format!("with unknown image ID {}", Digest::from(*image_id)),
+                };
+                write!(
+                    f,
+                    "proof was produced by program version {version} which this verifier does not accept"
+                )
+            }
+            VerifyError::CipherMismatch { actual, expected } => {
+                write!(
+                    f,
+                    "solution was encrypted with cipher ID {actual} ({}); expected cipher ID {expected} ({})",
+                    cipher::cipher_name(*actual).unwrap_or("unknown"),
+                    cipher::cipher_name(*expected).unwrap_or("unknown"),
+                )
+            }
+            VerifyError::FakeReceipt => f.write_str("receipt is fake"),
+            VerifyError::FakeReceiptClaimMismatch => {
+                f.write_str("fake receipt claim does not match image ID and journal")
+            }
+            VerifyError::Receipt(e) => write!(f, "receipt is invalid: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VerifyError::Receipt(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<VerificationError> for VerifyError {
+    fn from(e: VerificationError) -> Self {
+        VerifyError::Receipt(e)
+    }
+}
+
+/// The error returned when verifying a batch of proofs. Lists every proof which failed
+/// verification, by its index in the batch, along with the reason it was rejected.
+#[derive(Debug)]
+pub struct BatchVerifyError {
+    /// Failing proofs as `(index, error)` pairs, sorted by index.
+    pub failures: Vec<(usize, anyhow::Error)>,
+}
+
+impl BatchVerifyError {
+    /// Returns the indices of every proof which failed verification, in ascending order.
+    pub fn failed_indices(&self) -> Vec<usize> {
+        self.failures.iter().map(|(i, _)| *i).collect()
+    }
+}
+
+impl std::fmt::Display for BatchVerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} proofs in batch failed verification",
+            self.failures.len()
+        )?;
+        for (i, e) in &self.failures {
+            write!(f, "; proof {i}: {e}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for BatchVerifyError {}
@@ -0,0 +1,86 @@
+//! [BIP340-style tagged hashes](https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki#design)
+//! used for every hash-based derivation in this crate.
+//!
+//! A tagged hash of `msg` is `SHA256(SHA256(tag) || SHA256(tag) || version || msg)`, where
+//! `version` is the [`PROTOCOL_VERSION`] byte. Tagging gives each derivation its own domain,
+//! so that for example a Schnorr challenge can never be reinterpreted as a nonce, and the
+//! version byte ensures hashes from different protocol versions never collide.
+
+use common::kdf;
+use risc0_zkvm::sha::rust_crypto::{Digest as _, Sha256};
+use zeroize::Zeroizing;
+
+/// The version of the zkcp protocol, which is committed to by every tagged hash, and
+/// prefixed to every serialized proof.
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// Tag for the Schnorr challenge hash.
+pub(crate) const CHALLENGE_TAG: &[u8] = b"zkcp/challenge";
+
+/// Tag for the Schnorr secret nonce derivation.
+pub(crate) const NONCE_TAG: &[u8] = b"zkcp/nonce";
+
+/// Tag for hashing auxiliary randomness before mixing it into a nonce.
+pub(crate) const AUX_RAND_TAG: &[u8] = b"zkcp/aux_rand";
+
+/// Tag for the Schnorr challenge hash of a verifier attestation.
+pub(crate) const ATTESTATION_TAG: &[u8] = b"zkcp/attestation";
+
+/// Tag for the Schnorr secret nonce derivation of a verifier attestation.
+pub(crate) const ATTESTATION_NONCE_TAG: &[u8] = b"zkcp/attestation_nonce";
+
+/// Tag for the chacha nonce derivation.
+pub(crate) const CHACHA_NONCE_TAG: &[u8] = b"zkcp/chacha_nonce";
+
+/// Returns a SHA256 hasher which has already been fed `SHA256(tag) || SHA256(tag) || version`.
+/// The caller should then update it with the message and finalize it.
+pub(crate) fn tagged_hasher(tag: &[u8]) -> Sha256 {
+    let tag_hash = Sha256::digest(tag);
+    Sha256::new()
+        .chain_update(tag_hash)
+        .chain_update(tag_hash)
+        .chain_update([PROTOCOL_VERSION])
+}
+
+/// Derive a symmetric cipher key from `secret`, exactly as guest programs which use
+/// [`common::kdf`] do. Note that this hash is defined by the guest, so it is not versioned
+/// with [`PROTOCOL_VERSION`]; it is versioned by the program ID instead.
+pub(crate) fn derive_cipher_key(secret: &[u8; 32]) -> Zeroizing<[u8; 32]> {
+    let kdf_input = Zeroizing::new(kdf::cipher_key_kdf_input(secret));
+    Zeroizing::new(Sha256::digest(kdf_input.as_slice()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tagged_hasher() {
+        let tag_hash = Sha256::digest(b"zkcp/challenge");
+        let expected = Sha256::new()
+            .chain_update(tag_hash)
+            .chain_update(tag_hash)
+            .chain_update([PROTOCOL_VERSION])
+            .chain_update(b"hello")
+            .finalize();
+
+        assert_eq!(
+            tagged_hasher(CHALLENGE_TAG)
+                .chain_update(b"hello")
+                .finalize(),
+            expected
+        );
+        assert_ne!(
+            tagged_hasher(NONCE_TAG).chain_update(b"hello").finalize(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_cipher_key_tag_hash() {
+        assert_eq!(
+            <[u8; 32]>::from(Sha256::digest(kdf::CIPHER_KEY_TAG)),
+            kdf::CIPHER_KEY_TAG_HASH
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! A table of the image IDs of every guest program, in this build and in published releases.
+//!
+//! A program's image ID is a hash of its compiled ELF, so it changes whenever the program's
+//! source, its dependencies or the RISC0 toolchain change. Two releases of this crate will
+//! therefore usually produce proofs of the same program under different image IDs, and by
+//! default, each rejects the other's proofs.
+//!
+//! Verifiers which must interoperate with counterparties on other releases can look up the
+//! image IDs they are willing to accept here, and pass them to the `verify_with_allowed_ids`
+//! method of a proof. Only accept image IDs of releases you have audited: a proof is only
+//! as meaningful as the program which produced it.
+
+use std::fmt;
+
+use risc0_zkvm::sha::Digest;
+
+use crate::methods::{
+    DLOG_SECP256K1_SHA256_ID, DLOG_SECP256K1_SUDOKU_AEAD_ID, DLOG_SECP256K1_SUDOKU_BATCH_ID,
+    DLOG_SECP256K1_SUDOKU_CIPHER_ID, DLOG_SECP256K1_SUDOKU_ID, DLOG_SECP256K1_SUDOKU_MULTIKEY_ID,
+    DLOG_SECP256K1_SUDOKU_UNIQUE_ID, DLOG_SECP256K1_SUDOKU_V2_ID, SHA256_SUDOKU_AEAD_ID,
+    SHA256_SUDOKU_CIPHER_ID, SHA256_SUDOKU_COMPOSED_ID, SHA256_SUDOKU_ID, SHA256_SUDOKU_V2_ID,
+};
+
+/// The image ID of a guest program, as built by a particular release of this crate.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct KnownImageId {
+    /// The name of the guest program, such as `dlog-secp256k1-sudoku-v2`.
+    pub program: &'static str,
+    /// The version of this crate which built the program.
+    pub release: &'static str,
+    pub image_id: [u32; 8],
+}
+
+impl fmt::Display for KnownImageId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} from zkcp {} (image ID {})",
+            self.program,
+            self.release,
+            Digest::from(self.image_id)
+        )
+    }
+}
+
+const fn current(program: &'static str, image_id: [u32; 8]) -> KnownImageId {
+    KnownImageId {
+        program,
+        release: env!("CARGO_PKG_VERSION"),
+        image_id,
+    }
+}
+
+/// The image IDs of the programs built into this version of the crate.
+///
+/// These are whatever the local toolchain produced, so unless the guests were built with
+/// `RISC0_USE_DOCKER=1`, they may differ from those of the same version built elsewhere.
+pub const CURRENT_IMAGE_IDS: &[KnownImageId] = &[
+    current("dlog-secp256k1-sha256", DLOG_SECP256K1_SHA256_ID),
+    current("dlog-secp256k1-sudoku", DLOG_SECP256K1_SUDOKU_ID),
+    current("dlog-secp256k1-sudoku-aead", DLOG_SECP256K1_SUDOKU_AEAD_ID),
+    current(
+        "dlog-secp256k1-sudoku-batch",
+        DLOG_SECP256K1_SUDOKU_BATCH_ID,
+    ),
+    current(
+        "dlog-secp256k1-sudoku-cipher",
+        DLOG_SECP256K1_SUDOKU_CIPHER_ID,
+    ),
+    current(
+        "dlog-secp256k1-sudoku-multikey",
+        DLOG_SECP256K1_SUDOKU_MULTIKEY_ID,
+    ),
+    current(
+        "dlog-secp256k1-sudoku-unique",
+        DLOG_SECP256K1_SUDOKU_UNIQUE_ID,
+    ),
+    current("dlog-secp256k1-sudoku-v2", DLOG_SECP256K1_SUDOKU_V2_ID),
+    current("sha256-sudoku", SHA256_SUDOKU_ID),
+    current("sha256-sudoku-aead", SHA256_SUDOKU_AEAD_ID),
+    current("sha256-sudoku-cipher", SHA256_SUDOKU_CIPHER_ID),
+    current("sha256-sudoku-composed", SHA256_SUDOKU_COMPOSED_ID),
+    current("sha256-sudoku-v2", SHA256_SUDOKU_V2_ID),
+];
+
+/// The image IDs of the programs built by published releases of this crate.
+///
+/// This is empty until a release records its image IDs. When publishing a release, build it
+/// with `RISC0_USE_DOCKER=1` and append the output of `zkcp image-ids` here, so that anyone
+/// can reproduce the IDs, and later releases can recognize, and choose to accept, its proofs.
+pub const RELEASED_IMAGE_IDS: &[KnownImageId] = &[];
+
+/// Returns every known image ID: those of this build, followed by those of previous releases.
+pub fn known_image_ids() -> impl Iterator<Item = &'static KnownImageId> {
+    CURRENT_IMAGE_IDS.iter().chain(RELEASED_IMAGE_IDS)
+}
+
+/// Look up which program and release an image ID belongs to.
+pub fn lookup(image_id: [u32; 8]) -> Option<&'static KnownImageId> {
+    known_image_ids().find(|known| known.image_id == image_id)
+}
+
+/// Returns every known image ID of the program named `program`, across all releases.
+pub fn image_ids_for(program: &str) -> Vec<[u32; 8]> {
+    known_image_ids()
+        .filter(|known| known.program == program)
+        .map(|known| known.image_id)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::Program;
+    use crate::proofs::dlog_secp256k1_sudoku::Secp256k1DlogSudokuV2Program;
+
+    #[test]
+    fn test_known_image_ids() {
+        let id = Secp256k1DlogSudokuV2Program::id();
+        let known = lookup(id).unwrap();
+        assert_eq!(known.program, "dlog-secp256k1-sudoku-v2");
+        assert_eq!(known.release, env!("CARGO_PKG_VERSION"));
+        assert!(image_ids_for("dlog-secp256k1-sudoku-v2").contains(&id));
+        assert!(image_ids_for("dlog-secp256k1-sha256")
+            .iter()
+            .all(|&other| other != id));
+        assert_eq!(lookup([7u32; 8]), None);
+
+        // Every entry must be unique, or lookups would be ambiguous.
+        for (i, a) in known_image_ids().enumerate() {
+            for b in known_image_ids().skip(i + 1) {
+                assert_ne!(a.image_id, b.image_id, "{a} and {b} share an image ID");
+            }
+        }
+    }
+}
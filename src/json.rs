@@ -0,0 +1,176 @@
+//! A stable JSON representation of proofs, for clients such as web browsers which cannot
+//! decode [`borsh`].
+//!
+//! The `serde` implementations of the proof types mirror their Rust structure, including
+//! RISC0's own `Receipt` structure, which is very large and may change between RISC0
+//! releases. The representation here instead encodes the receipt as opaque borsh bytes, and
+//! adds a decoded view of the journal so that clients can read a proof's public outputs
+//! without understanding receipts at all.
+//!
+//! # Schema
+//!
+//! Byte strings are lowercase hex, except for the receipt, which is standard base64 with
+//! padding. Points are 33-byte compressed secp256k1 points, and image IDs are 32 bytes.
+//!
+//! [`Secp256k1DlogProof`][crate::proofs::dlog_secp256k1_generic::Secp256k1DlogProof]:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "image_id": "<hex, 32 bytes>",
+//!   "public_key": "<hex, 33 bytes>",
+//!   "public_nonce": "<hex, 33 bytes>",
+//!   "receipt": "<base64 borsh-encoded receipt>",
+//!   "journal": {
+//!     "challenge": "<hex, 32 bytes>",
+//!     "signature": "<hex, 32 bytes>",
+//!     "appendix": "<hex>"
+//!   }
+//! }
+//! ```
+//!
+//! [`Sha256Proof`][crate::proofs::sha256_generic::Sha256Proof]:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "image_id": "<hex, 32 bytes>",
+//!   "receipt": "<base64 borsh-encoded receipt>",
+//!   "journal": {
+//!     "hash": "<hex, 32 bytes>",
+//!     "appendix": "<hex>"
+//!   }
+//! }
+//! ```
+//!
+//! `version` is the [`PROTOCOL_VERSION`]. The journal view is redundant with the receipt,
+//! so decoding rejects any proof whose journal view does not match its receipt's journal,
+//! as well as proofs for a program other than the one expected. Decoding does not verify
+//! the proof.
+
+use anyhow::{bail, Context};
+use base64::Engine as _;
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::Receipt;
+use secp::Point;
+
+use crate::hashes::PROTOCOL_VERSION;
+
+/// The JSON representation of a
+/// [`Secp256k1DlogProof`][crate::proofs::dlog_secp256k1_generic::Secp256k1DlogProof].
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Secp256k1DlogProofJson {
+    pub version: u8,
+    pub image_id: String,
+    pub public_key: String,
+    pub public_nonce: String,
+    pub receipt: String,
+    pub journal: Secp256k1DlogJournalJson,
+}
+
+/// The decoded journal of a
+/// [`Secp256k1DlogProof`][crate::proofs::dlog_secp256k1_generic::Secp256k1DlogProof].
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Secp256k1DlogJournalJson {
+    pub challenge: String,
+    pub signature: String,
+    pub appendix: String,
+}
+
+/// The JSON representation of a [`Sha256Proof`][crate::proofs::sha256_generic::Sha256Proof].
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Sha256ProofJson {
+    pub version: u8,
+    pub image_id: String,
+    pub receipt: String,
+    pub journal: Sha256JournalJson,
+}
+
+/// The decoded journal of a [`Sha256Proof`][crate::proofs::sha256_generic::Sha256Proof].
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Sha256JournalJson {
+    pub hash: String,
+    pub appendix: String,
+}
+
+pub(crate) fn encode_image_id(id: [u32; 8]) -> String {
+    hex::encode(Digest::from(id).as_bytes())
+}
+
+pub(crate) fn encode_receipt(receipt: &Receipt) -> Result<String, anyhow::Error> {
+    Ok(base64::engine::general_purpose::STANDARD.encode(borsh::to_vec(receipt)?))
+}
+
+pub(crate) fn decode_receipt(receipt: &str) -> Result<Receipt, anyhow::Error> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(receipt)
+        .context("receipt is not valid base64")?;
+    borsh::from_slice(&bytes).context("receipt is not a valid borsh-encoded receipt")
+}
+
+pub(crate) fn decode_point(field: &str, hex_point: &str) -> Result<Point, anyhow::Error> {
+    let bytes = hex::decode(hex_point).with_context(|| format!("{field} is not valid hex"))?;
+    <[u8; 33]>::try_from(bytes.as_slice())
+        .ok()
+        .and_then(|bytes| Point::try_from(bytes).ok())
+        .with_context(|| format!("{field} is not a valid compressed secp256k1 point"))
+}
+
+/// Check the version and image ID fields of a JSON proof.
+pub(crate) fn check_header(
+    version: u8,
+    image_id: &str,
+    expected_id: [u32; 8],
+) -> Result<(), anyhow::Error> {
+    if version != PROTOCOL_VERSION {
+        bail!("unsupported proof format version {version}; expected {PROTOCOL_VERSION}");
+    }
+    let expected = encode_image_id(expected_id);
+    if !image_id.eq_ignore_ascii_case(&expected) {
+        bail!("proof is for program {image_id}; expected {expected}");
+    }
+    Ok(())
+}
+
+/// Check that a hex field of the journal view matches the bytes in the receipt's journal.
+pub(crate) fn check_journal_field(
+    field: &str,
+    hex_value: &str,
+    actual: &[u8],
+) -> Result<(), anyhow::Error> {
+    let value =
+        hex::decode(hex_value).with_context(|| format!("journal {field} is not valid hex"))?;
+    if value != actual {
+        bail!("journal {field} does not match the receipt journal");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_header() {
+        let id = [1u32, 2, 3, 4, 5, 6, 7, 8];
+        let image_id = encode_image_id(id);
+        assert_eq!(image_id.len(), 64);
+        assert_eq!(&image_id[..8], "01000000");
+
+        check_header(PROTOCOL_VERSION, &image_id, id).unwrap();
+        check_header(PROTOCOL_VERSION, &image_id.to_uppercase(), id).unwrap();
+        assert!(check_header(PROTOCOL_VERSION + 1, &image_id, id).is_err());
+        assert!(check_header(PROTOCOL_VERSION, &image_id, [0; 8]).is_err());
+    }
+
+    #[test]
+    fn test_decode_point() {
+        let point = secp::Scalar::reduce_from(&[3u8; 32]) * secp::G;
+        let point_hex = hex::encode(point.serialize());
+        assert_eq!(decode_point("public_key", &point_hex).unwrap(), point);
+
+        assert!(decode_point("public_key", "zz").is_err());
+        assert!(decode_point("public_key", &point_hex[2..]).is_err());
+        assert!(decode_point("public_key", &"05".repeat(33)).is_err());
+    }
+}
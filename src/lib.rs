@@ -1,6 +1,18 @@
+pub mod claim;
+pub mod envelope;
+pub mod error;
+pub mod hashes;
+pub mod image_ids;
+pub mod json;
 pub mod program;
 pub mod proofs;
+pub mod receipt;
+pub mod secret;
+pub mod verified;
 
+pub use common::cipher;
+#[cfg(feature = "solver")]
+pub use common::solver;
 pub use common::sudoku;
 pub use secp;
 
@@ -1,6 +1,403 @@
+use anyhow::bail;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::secret::Secret;
+
+/// Fixed-length data which is passed to a guest program as input, or read back from its
+/// journal, in the raw byte layout the guest uses.
+///
+/// Implemented for bytes, arrays and tuples, so that most layouts can be described by
+/// composing them. Tuple fields and array elements are laid out in order with no padding.
+pub trait GuestData: Sized {
+    /// The length of the encoding in bytes.
+    const LEN: usize;
+
+    /// Append exactly [`LEN`][Self::LEN] bytes encoding `self` to `buf`.
+    fn encode(&self, buf: &mut Vec<u8>);
+
+    /// Decode from exactly [`LEN`][Self::LEN] bytes. Callers must check the length first;
+    /// prefer [`from_bytes`][Self::from_bytes], which does.
+    fn decode(bytes: &[u8]) -> Result<Self, anyhow::Error>;
+
+    /// Encode `self` into a new buffer, which is zeroized when dropped in case `self` is
+    /// secret.
+    fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut buf = Zeroizing::new(Vec::with_capacity(Self::LEN));
+        self.encode(&mut buf);
+        buf
+    }
+
+    /// Decode from `bytes`, which must be exactly [`LEN`][Self::LEN] bytes long.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        if bytes.len() != Self::LEN {
+            bail!(
+                "expected {} bytes of guest data; got {}",
+                Self::LEN,
+                bytes.len()
+            );
+        }
+        Self::decode(bytes)
+    }
+}
+
+impl GuestData for () {
+    const LEN: usize = 0;
+
+    fn encode(&self, _buf: &mut Vec<u8>) {}
+
+    fn decode(_bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        Ok(())
+    }
+}
+
+impl GuestData for u8 {
+    const LEN: usize = 1;
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.push(*self);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        Ok(bytes[0])
+    }
+}
+
+impl<T: GuestData, const N: usize> GuestData for [T; N] {
+    const LEN: usize = N * T::LEN;
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        for item in self {
+            item.encode(buf);
+        }
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        let item_bytes = |i: usize| &bytes[i * T::LEN..][..T::LEN];
+
+        // Check every item first, so that the array can then be decoded in place. Collecting
+        // into a scratch `Vec` would leave a copy of any secret items in freed memory.
+        for i in 0..N {
+            T::decode(item_bytes(i))?;
+        }
+        Ok(std::array::from_fn(|i| {
+            T::decode(item_bytes(i)).expect("every item was decoded above")
+        }))
+    }
+}
+
+impl<T: GuestData + Zeroize> GuestData for Secret<T> {
+    const LEN: usize = T::LEN;
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.expose_secret().encode(buf);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        Ok(Secret::new(T::decode(bytes)?))
+    }
+}
+
+impl<A: GuestData, B: GuestData> GuestData for (A, B) {
+    const LEN: usize = A::LEN + B::LEN;
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.0.encode(buf);
+        self.1.encode(buf);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        let (a, b) = bytes.split_at(A::LEN);
+        Ok((A::decode(a)?, B::decode(b)?))
+    }
+}
+
+impl<A: GuestData, B: GuestData, C: GuestData> GuestData for (A, B, C) {
+    const LEN: usize = A::LEN + B::LEN + C::LEN;
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.0.encode(buf);
+        self.1.encode(buf);
+        self.2.encode(buf);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        let (a, rest) = bytes.split_at(A::LEN);
+        let (b, c) = rest.split_at(B::LEN);
+        Ok((A::decode(a)?, B::decode(b)?, C::decode(c)?))
+    }
+}
+
+/// A RISC0 guest program, which can be proven by one of the generic proof types.
+///
+/// The generic proof types feed the guest their own inputs first, such as a secret key,
+/// followed by the program's [`AuxInput`][Self::AuxInput]. The guest's journal likewise
+/// begins with the generic outputs, such as a Schnorr signature, followed by the program's
+/// [`Appendix`][Self::Appendix].
 pub trait Program {
+    /// The program-specific input to the guest.
+    type AuxInput: GuestData;
+
+    /// The program-specific output at the end of the journal.
+    type Appendix: GuestData;
+
     fn id() -> [u32; 8];
     fn elf() -> &'static [u8];
-    fn aux_input_len() -> usize;
-    fn appendix_len() -> usize;
+
+    fn aux_input_len() -> usize {
+        <Self::AuxInput as GuestData>::LEN
+    }
+    fn appendix_len() -> usize {
+        <Self::Appendix as GuestData>::LEN
+    }
+
+    /// Check the appendix of a journal, which is known to be
+    /// [`appendix_len`][Self::appendix_len] bytes long, for anything the type of a proof
+    /// of this program promises beyond its length.
+    ///
+    /// The generic proof types call this when constructing, deserializing and verifying
+    /// proofs. Any appendix is accepted by default.
+    fn check_appendix(_appendix: &[u8]) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+}
+
+/// A program which takes several secp256k1 secret keys as input, and outputs a Schnorr
+/// signature for each of them.
+pub trait MultiKeyProgram: Program {
+    fn key_count() -> usize;
+}
+
+/// Declare a guest [`Program`], and optionally a type alias for the proof of it.
+///
+/// The program is declared from the `_ELF` and `_ID` constants which `risc0_build` generates
+/// for its guest in `methods.rs`, and the [`AuxInput`][Program::AuxInput] and
+/// [`Appendix`][Program::Appendix] types which describe the guest's input and journal. Their
+/// lengths follow from [`GuestData::LEN`], so they need not be counted by hand. The guest
+/// crate must still be listed in `[package.metadata.risc0] methods`.
+///
+/// The program may take either one const parameter, such as `<const N: usize>`, or any
+/// number of bounded type parameters, such as `<C: GuestCipher>`, which are only used by the
+/// host. Two optional fields follow the appendix:
+///
+/// - `key_count` also implements [`MultiKeyProgram`], with the given number of keys.
+/// - `check_appendix` overrides [`Program::check_appendix`] with the given function.
+///
+/// The proof alias names one of the generic proof types, instantiated with `Self`, and takes
+/// the same parameters as the program. Its `prove` and `appendix_typed` methods then take
+/// and return the types declared here.
+///
+/// ```
+/// use zkcp::methods::{SHA256_SUDOKU_V2_ELF, SHA256_SUDOKU_V2_ID};
+/// use zkcp::proofs::sha256_generic::Sha256Proof;
+/// use zkcp::proofs::sudoku_data::{SudokuAppendix, SudokuAuxInput};
+///
+/// zkcp::zkcp_program! {
+///     /// Proves that a SHA256 preimage derives the key to a valid sudoku solution.
+///     pub struct MySudokuProgram {
+///         elf: SHA256_SUDOKU_V2_ELF,
+///         id: SHA256_SUDOKU_V2_ID,
+///         aux_input: SudokuAuxInput,
+///         appendix: SudokuAppendix,
+///     }
+///
+///     /// A proof of [`MySudokuProgram`].
+///     pub type MySudokuProof = Sha256Proof<Self>;
+/// }
+/// ```
+#[macro_export]
+macro_rules! zkcp_program {
+    (
+        @impl [$($impl_generics:tt)*] [$($args:tt)*] [$($alias_generics:tt)*] $program:ident {
+            elf: $elf:path,
+            id: $id:path,
+            aux_input: $aux_input:ty,
+            appendix: $appendix:ty
+            $(, key_count: $key_count:expr)?
+            $(, check_appendix: $check_appendix:expr)?
+            $(,)?
+        }
+        $($proof:tt)*
+    ) => {
+        impl<$($impl_generics)*> $crate::program::Program for $program<$($args)*> {
+            type AuxInput = $aux_input;
+            type Appendix = $appendix;
+
+            fn id() -> [u32; 8] {
+                $id
+            }
+            fn elf() -> &'static [u8] {
+                $elf
+            }
+            $(
+                fn check_appendix(appendix: &[u8]) -> ::anyhow::Result<()> {
+                    ($check_appendix)(appendix)
+                }
+            )?
+        }
+
+        $crate::zkcp_program!(
+            @multi_key [$($impl_generics)*] [$($args)*] $program $($key_count)?
+        );
+        $crate::zkcp_program!(
+            @proof [$($alias_generics)*] [$($args)*] $program $($proof)*
+        );
+    };
+
+    (@multi_key [$($impl_generics:tt)*] [$($args:tt)*] $program:ident) => {};
+    (@multi_key [$($impl_generics:tt)*] [$($args:tt)*] $program:ident $key_count:expr) => {
+        impl<$($impl_generics)*> $crate::program::MultiKeyProgram for $program<$($args)*> {
+            fn key_count() -> usize {
+                $key_count
+            }
+        }
+    };
+
+    (@proof [$($alias_generics:tt)*] [$($args:tt)*] $program:ident) => {};
+    (
+        @proof [$($alias_generics:tt)*] [$($args:tt)*] $program:ident
+        $(#[$proof_attr:meta])*
+        $proof_vis:vis type $proof:ident = $proof_generic:ident<Self>;
+    ) => {
+        $(#[$proof_attr])*
+        $proof_vis type $proof<$($alias_generics)*> = $proof_generic<$program<$($args)*>>;
+    };
+
+    (
+        $(#[$program_attr:meta])*
+        $program_vis:vis struct $program:ident { $($body:tt)* }
+        $($proof:tt)*
+    ) => {
+        $(#[$program_attr])*
+        #[derive(Copy, Debug, Clone, Eq, PartialEq, Hash)]
+        $program_vis struct $program;
+
+        $crate::zkcp_program!(@impl [] [] [] $program { $($body)* } $($proof)*);
+    };
+
+    (
+        $(#[$program_attr:meta])*
+        $program_vis:vis struct $program:ident<const $n:ident: usize> { $($body:tt)* }
+        $($proof:tt)*
+    ) => {
+        $(#[$program_attr])*
+        #[derive(Copy, Debug, Clone, Eq, PartialEq, Hash)]
+        $program_vis struct $program<const $n: usize>;
+
+        $crate::zkcp_program!(
+            @impl [const $n: usize] [$n] [const $n: usize] $program { $($body)* } $($proof)*
+        );
+    };
+
+    (
+        $(#[$program_attr:meta])*
+        $program_vis:vis struct $program:ident<$($param:ident: $bound:path),+> {
+            $($body:tt)*
+        }
+        $($proof:tt)*
+    ) => {
+        $(#[$program_attr])*
+        #[derive(Copy, Debug, Clone, Eq, PartialEq, Hash)]
+        $program_vis struct $program<$($param: $bound),+>(
+            ::core::marker::PhantomData<($($param,)+)>,
+        );
+
+        $crate::zkcp_program!(
+            @impl [$($param: $bound),+] [$($param),+] [$($param),+] $program { $($body)* } $($proof)*
+        );
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::methods::{DLOG_SECP256K1_SHA256_ELF, DLOG_SECP256K1_SHA256_ID};
+    use crate::proofs::dlog_secp256k1_generic::Secp256k1DlogProof;
+    use crate::proofs::dlog_secp256k1_multi_generic::Secp256k1MultiDlogProof;
+
+    crate::zkcp_program! {
+        struct TestProgram {
+            elf: DLOG_SECP256K1_SHA256_ELF,
+            id: DLOG_SECP256K1_SHA256_ID,
+            aux_input: ([u8; 12], Secret<[u8; 81]>),
+            appendix: [u8; 32],
+        }
+
+        type TestProof = Secp256k1DlogProof<Self>;
+    }
+
+    crate::zkcp_program! {
+        struct TestMultiKeyProgram<const N: usize> {
+            elf: DLOG_SECP256K1_SHA256_ELF,
+            id: DLOG_SECP256K1_SHA256_ID,
+            aux_input: [[u8; 2]; N],
+            appendix: [u8; N],
+            key_count: N,
+        }
+
+        type TestMultiKeyProof = Secp256k1MultiDlogProof<Self>;
+    }
+
+    trait Tag {
+        const TAG: u8;
+    }
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+    struct Seven;
+
+    impl Tag for Seven {
+        const TAG: u8 = 7;
+    }
+
+    fn check_tag<T: Tag>(appendix: &[u8]) -> Result<(), anyhow::Error> {
+        if appendix != [T::TAG] {
+            bail!("wrong tag");
+        }
+        Ok(())
+    }
+
+    crate::zkcp_program! {
+        struct TestTaggedProgram<T: Tag> {
+            elf: DLOG_SECP256K1_SHA256_ELF,
+            id: DLOG_SECP256K1_SHA256_ID,
+            aux_input: (),
+            appendix: u8,
+            check_appendix: check_tag::<T>,
+        }
+    }
+
+    #[test]
+    fn test_guest_data_layout() {
+        type Layout = ([u8; 3], u8, [([u8; 2], u8); 2]);
+        assert_eq!(Layout::LEN, 3 + 1 + 2 * 3);
+
+        let data: Layout = ([1, 2, 3], 4, [([5, 6], 7), ([8, 9], 10)]);
+        let bytes = data.to_bytes();
+        assert_eq!(bytes.as_slice(), &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(Layout::from_bytes(&bytes).unwrap(), data);
+
+        assert!(Layout::from_bytes(&bytes[1..]).is_err());
+        <()>::from_bytes(&[]).unwrap();
+        assert!(<()>::from_bytes(&[0]).is_err());
+    }
+
+    #[test]
+    fn test_zkcp_program_macro() {
+        assert_eq!(TestProgram::id(), DLOG_SECP256K1_SHA256_ID);
+        assert_eq!(TestProgram::elf(), DLOG_SECP256K1_SHA256_ELF);
+        assert_eq!(TestProgram::aux_input_len(), 12 + 81);
+        assert_eq!(TestProgram::appendix_len(), 32);
+
+        let _: fn(TestProof) -> Secp256k1DlogProof<TestProgram> = |proof| proof;
+        TestProgram::check_appendix(&[0; 32]).unwrap();
+
+        assert_eq!(TestMultiKeyProgram::<3>::key_count(), 3);
+        assert_eq!(TestMultiKeyProgram::<3>::aux_input_len(), 3 * 2);
+        assert_eq!(TestMultiKeyProgram::<3>::appendix_len(), 3);
+        let _: fn(TestMultiKeyProof<3>) -> Secp256k1MultiDlogProof<TestMultiKeyProgram<3>> =
+            |proof| proof;
+
+        assert_eq!(TestTaggedProgram::<Seven>::appendix_len(), 1);
+        TestTaggedProgram::<Seven>::check_appendix(&[7]).unwrap();
+        assert!(TestTaggedProgram::<Seven>::check_appendix(&[6]).is_err());
+    }
 }
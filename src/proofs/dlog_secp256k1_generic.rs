@@ -3,16 +3,60 @@ use std::marker::PhantomData;
 
 use anyhow::bail;
 use borsh::{BorshDeserialize, BorshSerialize};
-use risc0_zkvm::sha::rust_crypto::{Digest as _, Sha256};
-use risc0_zkvm::sha::Digest;
-use risc0_zkvm::{ExecutorEnv, LocalProver, Prover, ProverOpts, Receipt};
+use rand_core::{CryptoRng, RngCore};
+use rayon::prelude::*;
+use risc0_zkvm::sha::rust_crypto::Digest as _;
+use risc0_zkvm::sha::{Digest, Digestible as _};
+use risc0_zkvm::{ExecutorEnv, Receipt, ReceiptClaim};
 use secp::{MaybeScalar, Point, Scalar, G};
+use zeroize::Zeroizing;
 
-use crate::program::Program;
+use crate::claim::{DlogKeys, ProofClaim};
+use crate::error::{BatchVerifyError, VerifyError};
+use crate::hashes::{self, PROTOCOL_VERSION};
+use crate::json::{self, Secp256k1DlogJournalJson, Secp256k1DlogProofJson};
+use crate::program::{GuestData as _, Program};
+use crate::receipt::{self, VerifyOpts};
+use crate::verified::Verified;
 
-fn compute_challenge(id: [u32; 8], public_nonce: Point, public_key: Point) -> MaybeScalar {
+/// Derive the Schnorr nonce for proving that `secret_key` exhibits the properties of the
+/// program `id`, given the program's `aux_input`.
+///
+/// Similar to BIP340, the nonce is hedged by masking the secret key with a hash of the
+/// auxiliary randomness, if any. Without it, the nonce is fully deterministic.
+pub(super) fn derive_secret_nonce(
+    id: [u32; 8],
+    secret_key: Scalar,
+    aux_input: &[u8],
+    aux_rand: Option<&[u8; 32]>,
+) -> Scalar {
+    let mut nonce_key_bytes = Zeroizing::new(secret_key.serialize());
+    if let Some(aux_rand) = aux_rand {
+        let aux_rand_hash = hashes::tagged_hasher(hashes::AUX_RAND_TAG)
+            .chain_update(aux_rand)
+            .finalize();
+        for (k, r) in nonce_key_bytes.iter_mut().zip(aux_rand_hash) {
+            *k ^= r;
+        }
+    }
+
+    Scalar::reduce_from(
+        &hashes::tagged_hasher(hashes::NONCE_TAG)
+            .chain_update(Digest::from(id))
+            .chain_update(nonce_key_bytes.as_slice())
+            .chain_update(aux_input)
+            .finalize()
+            .into(),
+    )
+}
+
+pub(crate) fn compute_challenge(
+    id: [u32; 8],
+    public_nonce: Point,
+    public_key: Point,
+) -> MaybeScalar {
     MaybeScalar::reduce_from(
-        &Sha256::new()
+        &hashes::tagged_hasher(hashes::CHALLENGE_TAG)
             .chain_update(public_nonce.serialize())
             .chain_update(public_key.serialize())
             .chain_update(Digest::from(id))
@@ -24,17 +68,21 @@ fn compute_challenge(id: [u32; 8], public_nonce: Point, public_key: Point) -> Ma
 /// A generic proof that a secp256k1 discrete log (secret key) exhibits some custom properties.
 ///
 /// Generally this type is used to instantiate more application-specific proofs.
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+///
+/// The receipt can only be set through checked constructors, as described in
+/// [`crate::proofs`].
+#[derive(Clone, Debug, serde::Serialize)]
 pub struct Secp256k1DlogProof<P: Program> {
     pub public_key: Point,
     pub public_nonce: Point,
-    pub receipt: Receipt,
+    receipt: Receipt,
 
     phantom: PhantomData<P>,
 }
 
 impl<P: Program> BorshSerialize for Secp256k1DlogProof<P> {
     fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        BorshSerialize::serialize(&PROTOCOL_VERSION, writer)?;
         BorshSerialize::serialize(&self.public_key.serialize(), writer)?;
         BorshSerialize::serialize(&self.public_nonce.serialize(), writer)?;
         BorshSerialize::serialize(&self.receipt, writer)?;
@@ -44,6 +92,14 @@ impl<P: Program> BorshSerialize for Secp256k1DlogProof<P> {
 
 impl<P: Program> BorshDeserialize for Secp256k1DlogProof<P> {
     fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let version: u8 = BorshDeserialize::deserialize_reader(reader)?;
+        if version != PROTOCOL_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported proof format version {version}; expected {PROTOCOL_VERSION}"),
+            ));
+        }
+
         let public_key_bytes: [u8; 33] = BorshDeserialize::deserialize_reader(reader)?;
         let public_key = Point::try_from(public_key_bytes)
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
@@ -54,20 +110,46 @@ impl<P: Program> BorshDeserialize for Secp256k1DlogProof<P> {
 
         let receipt: Receipt = BorshDeserialize::deserialize_reader(reader)?;
 
-        let proof = Secp256k1DlogProof {
-            public_key,
-            public_nonce,
-            receipt,
-            phantom: PhantomData,
-        };
-        Ok(proof)
+        Self::from_receipt(public_key, public_nonce, receipt)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Mirrors the serde representation of [`Secp256k1DlogProof`], without any validation. The
+/// empty `phantom` field is ignored.
+#[derive(serde::Deserialize)]
+#[serde(rename = "Secp256k1DlogProof")]
+struct UncheckedSecp256k1DlogProof {
+    public_key: Point,
+    public_nonce: Point,
+    receipt: Receipt,
+}
+
+impl<'de, P: Program> serde::Deserialize<'de> for Secp256k1DlogProof<P> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let unchecked = UncheckedSecp256k1DlogProof::deserialize(deserializer)?;
+        Self::from_receipt(
+            unchecked.public_key,
+            unchecked.public_nonce,
+            unchecked.receipt,
+        )
+        .map_err(serde::de::Error::custom)
     }
 }
 
 impl<P: Program> Secp256k1DlogProof<P> {
-    /// Create a zk-STARK proof that a secp256k1 secret key exhibits some arbitrary properties
-    /// determined by the RISCV program `P`.
-    pub fn prove_custom(secret_key: Scalar, aux_input: &[u8]) -> Result<Self, anyhow::Error> {
+    /// Serialize the secret guest input for proving that `secret_key` exhibits the
+    /// properties determined by `P`. Returns the input along with the public key and
+    /// public nonce which the resulting proof will be bound to.
+    ///
+    /// If `aux_rand` is `None`, every input is derived deterministically from `secret_key`
+    /// and `aux_input`, so the journal output by the guest is always the same for the same
+    /// inputs. The returned buffer contains secret material and is zeroized when dropped.
+    fn guest_input(
+        secret_key: Scalar,
+        aux_input: &[u8],
+        aux_rand: Option<&[u8; 32]>,
+    ) -> Result<(Zeroizing<Vec<u8>>, Point, Point), anyhow::Error> {
         if aux_input.len() != P::aux_input_len() {
             bail!(
                 "expected aux_input to prover of len {}; got {}",
@@ -76,47 +158,153 @@ impl<P: Program> Secp256k1DlogProof<P> {
             );
         }
 
-        let secret_key_bytes = secret_key.serialize();
-
-        let secret_nonce = Scalar::reduce_from(
-            &Sha256::new()
-                .chain_update(Digest::from(P::id()))
-                .chain_update(secret_key_bytes)
-                .chain_update(aux_input)
-                .chain_update(b"secp256k1_nonce")
-                .finalize()
-                .into(),
-        );
-        let secret_nonce_bytes = secret_nonce.serialize();
+        let secret_key_bytes = Zeroizing::new(secret_key.serialize());
+        let secret_nonce = derive_secret_nonce(P::id(), secret_key, aux_input, aux_rand);
+        let secret_nonce_bytes = Zeroizing::new(secret_nonce.serialize());
 
         let public_key = secret_key * G;
         let public_nonce = secret_nonce * G;
 
         let challenge = compute_challenge(P::id(), public_nonce, public_key);
 
-        let env = ExecutorEnv::builder()
-            .write_slice(&secret_key_bytes)
-            .write_slice(&secret_nonce_bytes)
-            .write_slice(&challenge.serialize())
-            .write_slice(aux_input)
-            .build()?;
+        let mut guest_input = Zeroizing::new(Vec::with_capacity(96 + aux_input.len()));
+        guest_input.extend_from_slice(secret_key_bytes.as_slice());
+        guest_input.extend_from_slice(secret_nonce_bytes.as_slice());
+        guest_input.extend_from_slice(&challenge.serialize());
+        guest_input.extend_from_slice(aux_input);
+
+        Ok((guest_input, public_key, public_nonce))
+    }
+
+    /// Create a zk-STARK proof that a secp256k1 secret key exhibits some arbitrary properties
+    /// determined by the RISCV program `P`.
+    ///
+    /// # Determinism
+    ///
+    /// Proof generation is deterministic with respect to everything the proof _asserts._
+    /// The Schnorr nonce and any other guest inputs are derived from `secret_key` and
+    /// `aux_input`, so proving the same inputs twice always yields the same `public_key`,
+    /// `public_nonce` and journal, and thus the same [`claim_digest`][Self::claim_digest].
+    ///
+    /// The zk-STARK seal inside the [`Receipt`] is _not_ deterministic, because the RISC0
+    /// prover randomizes it to preserve zero-knowledge. Two receipts for the same inputs will
+    /// therefore differ byte-for-byte, but they will always prove the same claim. Parties
+    /// who want to agree on what a proof asserts should compare claim digests, not receipts.
+    pub fn prove_custom(secret_key: Scalar, aux_input: &[u8]) -> Result<Self, anyhow::Error> {
+        Self::prove_hedged(secret_key, aux_input, None, &[])
+    }
+
+    /// Create a zk-STARK proof as with [`prove_custom`][Self::prove_custom], from the typed
+    /// [`AuxInput`][Program::AuxInput] of the program `P`.
+    pub fn prove(secret_key: Scalar, aux_input: &P::AuxInput) -> Result<Self, anyhow::Error> {
+        Self::prove_custom(secret_key, &aux_input.to_bytes())
+    }
+
+    /// Create a zk-STARK proof as with [`prove_custom_with_rng`][Self::prove_custom_with_rng],
+    /// from the typed [`AuxInput`][Program::AuxInput] of the program `P`.
+    pub fn prove_with_rng<R: RngCore + CryptoRng>(
+        secret_key: Scalar,
+        aux_input: &P::AuxInput,
+        rng: &mut R,
+    ) -> Result<Self, anyhow::Error> {
+        Self::prove_custom_with_rng(secret_key, &aux_input.to_bytes(), rng)
+    }
+
+    /// Create a zk-STARK proof as with [`prove_custom`][Self::prove_custom], for a program
+    /// `P` which verifies other receipts with `env::verify`.
+    ///
+    /// Each receipt the guest verifies must be given in `assumptions`. The resulting receipt
+    /// is only valid if every assumption is, so the program can build on facts already
+    /// proven, such as `SHA256(k) = h` from a
+    /// [`Secp256k1DlogSha256Proof`][super::dlog_secp256k1_sha256::Secp256k1DlogSha256Proof],
+    /// without proving them again.
+    pub fn prove_custom_with_assumptions(
+        secret_key: Scalar,
+        aux_input: &[u8],
+        assumptions: &[Receipt],
+    ) -> Result<Self, anyhow::Error> {
+        Self::prove_hedged(secret_key, aux_input, None, assumptions)
+    }
+
+    /// Create a zk-STARK proof as with [`prove_custom`][Self::prove_custom], but hedge the
+    /// Schnorr nonce with 32 bytes of randomness drawn from `rng`, in the style of BIP340's
+    /// auxiliary randomness.
+    ///
+    /// The nonce remains safe even if `rng` is broken, but with a working `rng`, proving the
+    /// same secret key twice with identical `aux_input` no longer reuses the same nonce.
+    /// In exchange, proof generation is no longer deterministic: the journal and
+    /// [`claim_digest`][Self::claim_digest] will differ every time.
+    pub fn prove_custom_with_rng<R: RngCore + CryptoRng>(
+        secret_key: Scalar,
+        aux_input: &[u8],
+        rng: &mut R,
+    ) -> Result<Self, anyhow::Error> {
+        let mut aux_rand = Zeroizing::new([0u8; 32]);
+        rng.fill_bytes(aux_rand.as_mut_slice());
+        Self::prove_hedged(secret_key, aux_input, Some(&*aux_rand), &[])
+    }
+
+    fn prove_hedged(
+        secret_key: Scalar,
+        aux_input: &[u8],
+        aux_rand: Option<&[u8; 32]>,
+        assumptions: &[Receipt],
+    ) -> Result<Self, anyhow::Error> {
+        let (guest_input, public_key, public_nonce) =
+            Self::guest_input(secret_key, aux_input, aux_rand)?;
+
+        // Stream the input from our own buffer, which we can zeroize afterwards.
+        let env = receipt::executor_env(guest_input.as_slice(), assumptions)?;
 
         // This call takes a while.
-        let prove_info =
-            LocalProver::new("local").prove_with_opts(env, P::elf(), &ProverOpts::fast())?;
+        let receipt = receipt::prove::<P>(env)?;
+
+        Self::from_receipt(public_key, public_nonce, receipt)
+    }
 
+    /// Construct a proof from a receipt, and the public key and public nonce which its
+    /// Schnorr signature is bound to.
+    ///
+    /// Fails if the journal is not a valid output of `P`. This does not verify the proof.
+    pub fn from_receipt(
+        public_key: Point,
+        public_nonce: Point,
+        receipt: Receipt,
+    ) -> Result<Self, anyhow::Error> {
         let proof = Secp256k1DlogProof {
-            receipt: prove_info.receipt,
             public_key,
             public_nonce,
+            receipt,
             phantom: PhantomData,
         };
-
         proof.check_journal_length()?;
-
         Ok(proof)
     }
 
+    /// Execute the guest program without proving, and return the journal it outputs.
+    #[cfg(test)]
+    pub(crate) fn execute_custom(
+        secret_key: Scalar,
+        aux_input: &[u8],
+    ) -> Result<Vec<u8>, anyhow::Error> {
+        let (guest_input, _, _) = Self::guest_input(secret_key, aux_input, None)?;
+        let env = ExecutorEnv::builder()
+            .stdin(guest_input.as_slice())
+            .build()?;
+        let session_info = risc0_zkvm::default_executor().execute(env, P::elf())?;
+        Ok(session_info.journal.bytes)
+    }
+
+    /// Return a reference to the receipt of the proof.
+    pub fn receipt(&self) -> &Receipt {
+        &self.receipt
+    }
+
+    /// Consume the proof and return its receipt.
+    pub fn into_receipt(self) -> Receipt {
+        self.receipt
+    }
+
     /// Return a reference the bytes of the RISC0 guest program output (AKA the journal).
     pub fn journal(&self) -> &[u8] {
         &self.receipt.journal.bytes
@@ -128,25 +316,57 @@ impl<P: Program> Secp256k1DlogProof<P> {
     /// - Appendix:          P::appendix_len() bytes
     fn check_journal_length(&self) -> Result<(), anyhow::Error> {
         if self.journal().len() != 64 + P::appendix_len() {
-            bail!(
-                "journal is incorrect length {}; expected {}",
-                self.journal().len(),
-                64 + P::appendix_len()
-            );
+            bail!(VerifyError::JournalLength {
+                actual: self.journal().len(),
+                expected: 64 + P::appendix_len(),
+            });
+        }
+        P::check_appendix(self.appendix())
+    }
+
+    /// Return the SHA256 digest of the journal.
+    pub fn journal_digest(&self) -> Digest {
+        self.receipt.journal.digest()
+    }
+
+    /// Return the digest of the claim this proof makes: that the program `P` executed
+    /// successfully and output the journal of this proof.
+    ///
+    /// The claim digest commits to the image ID and the journal, which in turn commits to
+    /// the Schnorr signature over `public_key` and `public_nonce`. Two parties can compare
+    /// claim digests to agree on what a proof asserts without exchanging the whole receipt.
+    /// Note that the claim digest is computed locally; it does not imply the proof is valid.
+    pub fn claim_digest(&self) -> Digest {
+        ReceiptClaim::ok(P::id(), self.journal().to_vec()).digest()
+    }
+
+    /// Return the public outputs of this proof, detached from its receipt. See
+    /// [`crate::claim`].
+    pub fn claim(&self) -> ProofClaim {
+        ProofClaim {
+            image_id: P::id(),
+            journal: self.journal().to_vec(),
+            dlog_keys: Some(DlogKeys {
+                public_key: self.public_key,
+                public_nonce: self.public_nonce,
+            }),
         }
-        Ok(())
     }
 
     /// Parse and return the challenge scalar used to create the Schnorr signature,
     /// from the guest output journal.
     pub fn challenge(&self) -> Result<MaybeScalar, anyhow::Error> {
-        Ok(MaybeScalar::try_from(&self.journal()[0..32])?)
+        let scalar = MaybeScalar::try_from(&self.journal()[0..32])
+            .map_err(|_| VerifyError::InvalidScalar)?;
+        Ok(scalar)
     }
 
     /// Parse and return the Schnorr signature scalar `s` from the
     /// guest output journal.
     pub fn signature(&self) -> Result<MaybeScalar, anyhow::Error> {
-        Ok(MaybeScalar::try_from(&self.journal()[32..64])?)
+        let scalar = MaybeScalar::try_from(&self.journal()[32..64])
+            .map_err(|_| VerifyError::InvalidScalar)?;
+        Ok(scalar)
     }
 
     /// Return a reference to the _appendix,_ which refers to any journal output
@@ -159,9 +379,18 @@ impl<P: Program> Secp256k1DlogProof<P> {
         &self.journal()[64..]
     }
 
+    /// Decode the [`appendix`][Self::appendix] as the typed [`Appendix`][Program::Appendix]
+    /// of the program `P`.
+    pub fn appendix_typed(&self) -> Result<P::Appendix, anyhow::Error> {
+        P::Appendix::from_bytes(self.appendix())
+    }
+
     /// Serialize the proof to a compact vector of bytes. "Compact" is a relative term though,
     /// as zk-STARK proofs will generally be several hundred kilobytes.
     ///
+    /// The output is prefixed with the [`PROTOCOL_VERSION`] byte, so that proofs from
+    /// different protocol versions are never confused with one another.
+    ///
     /// We use [`borsh`](https://github.com/near/borsh-rs) for binary serialization.
     pub fn to_vec(&self) -> io::Result<Vec<u8>> {
         borsh::to_vec(self)
@@ -174,22 +403,197 @@ impl<P: Program> Secp256k1DlogProof<P> {
         borsh::from_slice(bytes)
     }
 
+    /// Serialize the proof to the stable JSON representation described in [`crate::json`].
+    pub fn to_json(&self) -> Result<String, anyhow::Error> {
+        let proof_json = Secp256k1DlogProofJson {
+            version: PROTOCOL_VERSION,
+            image_id: json::encode_image_id(P::id()),
+            public_key: hex::encode(self.public_key.serialize()),
+            public_nonce: hex::encode(self.public_nonce.serialize()),
+            receipt: json::encode_receipt(&self.receipt)?,
+            journal: Secp256k1DlogJournalJson {
+                challenge: hex::encode(&self.journal()[..32]),
+                signature: hex::encode(&self.journal()[32..64]),
+                appendix: hex::encode(self.appendix()),
+            },
+        };
+        Ok(serde_json::to_string(&proof_json)?)
+    }
+
+    /// Deserialize a proof from the JSON representation described in [`crate::json`].
+    ///
+    /// Fails if the proof is for a program other than `P`, or if its journal view does not
+    /// match the receipt. This does not verify the proof.
+    pub fn from_json(s: &str) -> Result<Self, anyhow::Error> {
+        let proof_json: Secp256k1DlogProofJson = serde_json::from_str(s)?;
+        json::check_header(proof_json.version, &proof_json.image_id, P::id())?;
+
+        let proof = Self::from_receipt(
+            json::decode_point("public_key", &proof_json.public_key)?,
+            json::decode_point("public_nonce", &proof_json.public_nonce)?,
+            json::decode_receipt(&proof_json.receipt)?,
+        )?;
+
+        let journal = &proof_json.journal;
+        json::check_journal_field("challenge", &journal.challenge, &proof.journal()[..32])?;
+        json::check_journal_field("signature", &journal.signature, &proof.journal()[32..64])?;
+        json::check_journal_field("appendix", &journal.appendix, proof.appendix())?;
+        Ok(proof)
+    }
+
     /// Verify the Schnorr signature, and then the zk-STARK proof of computational integrity.
     /// Returns `Ok` if the program `P` was executed correctly AND the secp256k1 Schnorr
     /// signature is valid.
+    ///
+    /// Fake receipts produced in dev mode are always rejected.
     pub fn verify(&self) -> Result<(), anyhow::Error> {
-        let challenge = compute_challenge(P::id(), self.public_nonce, self.public_key);
-        if challenge != self.challenge()? {
-            bail!("journal challenge does not match computed challenge");
-        }
+        self.verify_with_opts(&VerifyOpts::default())
+    }
+
+    /// Verify the proof as with [`verify`][Self::verify], but with custom options.
+    pub fn verify_with_opts(&self, opts: &VerifyOpts) -> Result<(), anyhow::Error> {
+        self.verify_with_allowed_ids_and_opts(&[P::id()], opts)
+    }
+
+    /// Verify the proof as with [`verify`][Self::verify], but accept proofs produced by any
+    /// of the programs in `allowed_ids`, rather than only by `P` as built into this crate.
+    ///
+    /// This lets a verifier accept proofs from counterparties using other releases of this
+    /// crate, whose builds of `P` have different image IDs. See [`crate::image_ids`] for the
+    /// image IDs of previous releases. Proofs from any other program are rejected with
+    /// [`VerifyError::ImageIdNotAllowed`].
+    pub fn verify_with_allowed_ids(&self, allowed_ids: &[[u32; 8]]) -> Result<(), anyhow::Error> {
+        self.verify_with_allowed_ids_and_opts(allowed_ids, &VerifyOpts::default())
+    }
+
+    /// Verify the proof as with [`verify_with_allowed_ids`][Self::verify_with_allowed_ids],
+    /// but with custom options.
+    pub fn verify_with_allowed_ids_and_opts(
+        &self,
+        allowed_ids: &[[u32; 8]],
+        opts: &VerifyOpts,
+    ) -> Result<(), anyhow::Error> {
+        // The challenge commits to the image ID, so check it first, to report a proof from
+        // another release as such rather than as a bad signature.
+        let image_id = receipt::check_allowed_image_id(&self.receipt, allowed_ids)?;
 
-        let s = self.signature()?;
+        let (challenge, s) = self.check_challenge(image_id)?;
         if s * G != self.public_nonce + self.public_key * challenge {
-            bail!("schnorr signature is invalid");
+            bail!(VerifyError::InvalidSignature);
         }
 
-        self.receipt.verify(P::id())?;
+        receipt::verify_image_id(&self.receipt, image_id, opts)?;
 
         Ok(())
     }
+
+    /// Check the journal length and the Schnorr challenge for the program `image_id`, and
+    /// return the challenge and signature scalar.
+    fn check_challenge(
+        &self,
+        image_id: [u32; 8],
+    ) -> Result<(MaybeScalar, MaybeScalar), anyhow::Error> {
+        self.check_journal_length()?;
+
+        let challenge = compute_challenge(image_id, self.public_nonce, self.public_key);
+        if challenge != self.challenge()? {
+            bail!(VerifyError::ChallengeMismatch);
+        }
+        Ok((challenge, self.signature()?))
+    }
+
+    /// Verify the proof, and wrap it in a [`Verified`] marker if it is valid.
+    pub fn into_verified(self) -> Result<Verified<Self>, anyhow::Error> {
+        self.into_verified_with_opts(&VerifyOpts::default())
+    }
+
+    /// Verify the proof with custom options, and wrap it in a [`Verified`] marker if it is valid.
+    pub fn into_verified_with_opts(
+        self,
+        opts: &VerifyOpts,
+    ) -> Result<Verified<Self>, anyhow::Error> {
+        self.verify_with_opts(opts)?;
+        Ok(Verified::new(self))
+    }
+}
+
+/// Verify many proofs at once, as with [`Secp256k1DlogProof::verify`], checking them in
+/// parallel.
+///
+/// Fake receipts produced in dev mode are always rejected. See
+/// [`verify_batch_with_opts`] for details.
+pub fn verify_batch<P: Program + Sync>(
+    proofs: &[Secp256k1DlogProof<P>],
+) -> Result<(), BatchVerifyError> {
+    verify_batch_with_opts(proofs, &VerifyOpts::default())
+}
+
+/// Verify many proofs at once, with custom options.
+///
+/// Each proof is verified exactly as [`Secp256k1DlogProof::verify_with_opts`] would, on its
+/// own thread. The work is the same as verifying every proof in turn, but spread across
+/// all cores. Returns a [`BatchVerifyError`] listing every proof which failed, once each,
+/// by index, along with the reason.
+pub fn verify_batch_with_opts<P: Program + Sync>(
+    proofs: &[Secp256k1DlogProof<P>],
+    opts: &VerifyOpts,
+) -> Result<(), BatchVerifyError> {
+    let failures: Vec<(usize, anyhow::Error)> = proofs
+        .par_iter()
+        .enumerate()
+        .filter_map(|(i, proof)| proof.verify_with_opts(opts).err().map(|e| (i, e)))
+        .collect();
+
+    if failures.is_empty() {
+        return Ok(());
+    }
+    Err(BatchVerifyError { failures })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proofs::dlog_secp256k1_sha256::Secp256k1DlogSha256Program;
+
+    #[test]
+    fn test_deterministic_journal() {
+        let secret_key = Scalar::reduce_from(&[3u8; 32]);
+
+        let journal1 =
+            Secp256k1DlogProof::<Secp256k1DlogSha256Program>::execute_custom(secret_key, b"")
+                .unwrap();
+        let journal2 =
+            Secp256k1DlogProof::<Secp256k1DlogSha256Program>::execute_custom(secret_key, b"")
+                .unwrap();
+        assert_eq!(journal1, journal2);
+
+        let other_key = Scalar::reduce_from(&[4u8; 32]);
+        let journal3 =
+            Secp256k1DlogProof::<Secp256k1DlogSha256Program>::execute_custom(other_key, b"")
+                .unwrap();
+        assert_ne!(journal1, journal3);
+    }
+
+    #[test]
+    fn test_hedged_secret_nonce() {
+        let id = Secp256k1DlogSha256Program::id();
+        let secret_key = Scalar::reduce_from(&[3u8; 32]);
+
+        let deterministic = derive_secret_nonce(id, secret_key, b"", None);
+        assert_eq!(
+            deterministic,
+            derive_secret_nonce(id, secret_key, b"", None)
+        );
+
+        let hedged = derive_secret_nonce(id, secret_key, b"", Some(&[0u8; 32]));
+        assert_ne!(hedged, deterministic);
+        assert_eq!(
+            hedged,
+            derive_secret_nonce(id, secret_key, b"", Some(&[0u8; 32]))
+        );
+        assert_ne!(
+            hedged,
+            derive_secret_nonce(id, secret_key, b"", Some(&[1u8; 32]))
+        );
+    }
 }
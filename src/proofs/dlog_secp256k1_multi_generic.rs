@@ -0,0 +1,425 @@
+use std::io;
+use std::marker::PhantomData;
+
+use anyhow::bail;
+use borsh::{BorshDeserialize, BorshSerialize};
+use rand_core::{CryptoRng, RngCore};
+use risc0_zkvm::sha::{Digest, Digestible as _};
+use risc0_zkvm::{ExecutorEnv, Receipt, ReceiptClaim};
+use secp::{MaybeScalar, Point, Scalar, G};
+use zeroize::Zeroizing;
+
+use super::dlog_secp256k1_generic::{compute_challenge, derive_secret_nonce};
+use crate::error::VerifyError;
+use crate::hashes::PROTOCOL_VERSION;
+use crate::program::{GuestData as _, MultiKeyProgram};
+use crate::receipt::{self, VerifyOpts};
+use crate::verified::Verified;
+
+/// A generic proof that several secp256k1 discrete logs (secret keys) together exhibit some
+/// custom properties. This generalizes [`Secp256k1DlogProof`][super::dlog_secp256k1_generic::Secp256k1DlogProof]
+/// to `P::key_count()` keys, with one Schnorr signature per key in the journal.
+///
+/// Generally this type is used to instantiate more application-specific proofs.
+///
+/// The keys, nonces and receipt can only be set through checked constructors, as described
+/// in [`crate::proofs`].
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct Secp256k1MultiDlogProof<P: MultiKeyProgram> {
+    public_keys: Vec<Point>,
+    public_nonces: Vec<Point>,
+    receipt: Receipt,
+
+    phantom: PhantomData<P>,
+}
+
+impl<P: MultiKeyProgram> BorshSerialize for Secp256k1MultiDlogProof<P> {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        BorshSerialize::serialize(&PROTOCOL_VERSION, writer)?;
+        for public_key in &self.public_keys {
+            BorshSerialize::serialize(&public_key.serialize(), writer)?;
+        }
+        for public_nonce in &self.public_nonces {
+            BorshSerialize::serialize(&public_nonce.serialize(), writer)?;
+        }
+        BorshSerialize::serialize(&self.receipt, writer)?;
+        Ok(())
+    }
+}
+
+fn deserialize_points<R: io::Read>(reader: &mut R, count: usize) -> io::Result<Vec<Point>> {
+    let mut points = Vec::with_capacity(count);
+    for _ in 0..count {
+        let point_bytes: [u8; 33] = BorshDeserialize::deserialize_reader(reader)?;
+        let point = Point::try_from(point_bytes)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        points.push(point);
+    }
+    Ok(points)
+}
+
+impl<P: MultiKeyProgram> BorshDeserialize for Secp256k1MultiDlogProof<P> {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let version: u8 = BorshDeserialize::deserialize_reader(reader)?;
+        if version != PROTOCOL_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported proof format version {version}; expected {PROTOCOL_VERSION}"),
+            ));
+        }
+
+        let public_keys = deserialize_points(reader, P::key_count())?;
+        let public_nonces = deserialize_points(reader, P::key_count())?;
+        let receipt: Receipt = BorshDeserialize::deserialize_reader(reader)?;
+
+        Self::from_receipt(public_keys, public_nonces, receipt)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Mirrors the serde representation of [`Secp256k1MultiDlogProof`], without any validation.
+/// The empty `phantom` field is ignored.
+#[derive(serde::Deserialize)]
+#[serde(rename = "Secp256k1MultiDlogProof")]
+struct UncheckedSecp256k1MultiDlogProof {
+    public_keys: Vec<Point>,
+    public_nonces: Vec<Point>,
+    receipt: Receipt,
+}
+
+impl<'de, P: MultiKeyProgram> serde::Deserialize<'de> for Secp256k1MultiDlogProof<P> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let unchecked = UncheckedSecp256k1MultiDlogProof::deserialize(deserializer)?;
+        Self::from_receipt(
+            unchecked.public_keys,
+            unchecked.public_nonces,
+            unchecked.receipt,
+        )
+        .map_err(serde::de::Error::custom)
+    }
+}
+
+impl<P: MultiKeyProgram> Secp256k1MultiDlogProof<P> {
+    /// Serialize the secret guest input for proving that `secret_keys` exhibit the
+    /// properties determined by `P`. Returns the input along with the public keys and
+    /// public nonces which the resulting proof will be bound to.
+    ///
+    /// Guest input:
+    /// - key count: 4 bytes, big-endian
+    /// - for each key: secret key, secret nonce and challenge, 32 bytes each
+    /// - aux input: P::aux_input_len() bytes
+    ///
+    /// Each nonce is derived as in [`Secp256k1DlogProof`][super::dlog_secp256k1_generic::Secp256k1DlogProof],
+    /// with the key's index prepended to `aux_input`. The returned buffer contains secret
+    /// material and is zeroized when dropped.
+    fn guest_input(
+        secret_keys: &[Scalar],
+        aux_input: &[u8],
+        aux_rand: Option<&[u8; 32]>,
+    ) -> Result<(Zeroizing<Vec<u8>>, Vec<Point>, Vec<Point>), anyhow::Error> {
+        if secret_keys.len() != P::key_count() {
+            bail!(
+                "expected {} secret keys; got {}",
+                P::key_count(),
+                secret_keys.len()
+            );
+        } else if aux_input.len() != P::aux_input_len() {
+            bail!(
+                "expected aux_input to prover of len {}; got {}",
+                P::aux_input_len(),
+                aux_input.len()
+            );
+        }
+
+        let mut guest_input = Zeroizing::new(Vec::with_capacity(
+            4 + 96 * secret_keys.len() + aux_input.len(),
+        ));
+        guest_input.extend_from_slice(&(secret_keys.len() as u32).to_be_bytes());
+
+        let mut public_keys = Vec::with_capacity(secret_keys.len());
+        let mut public_nonces = Vec::with_capacity(secret_keys.len());
+        for (i, &secret_key) in secret_keys.iter().enumerate() {
+            let indexed_aux_input =
+                Zeroizing::new([&(i as u32).to_be_bytes()[..], aux_input].concat());
+            let secret_nonce =
+                derive_secret_nonce(P::id(), secret_key, &indexed_aux_input, aux_rand);
+
+            let public_key = secret_key * G;
+            let public_nonce = secret_nonce * G;
+            let challenge = compute_challenge(P::id(), public_nonce, public_key);
+
+            guest_input.extend_from_slice(Zeroizing::new(secret_key.serialize()).as_slice());
+            guest_input.extend_from_slice(Zeroizing::new(secret_nonce.serialize()).as_slice());
+            guest_input.extend_from_slice(&challenge.serialize());
+
+            public_keys.push(public_key);
+            public_nonces.push(public_nonce);
+        }
+        guest_input.extend_from_slice(aux_input);
+
+        Ok((guest_input, public_keys, public_nonces))
+    }
+
+    /// Create a zk-STARK proof that a set of secp256k1 secret keys exhibit some arbitrary
+    /// properties determined by the RISCV program `P`.
+    ///
+    /// Proof generation is deterministic with respect to everything the proof asserts,
+    /// in the same way as [`Secp256k1DlogProof::prove_custom`][super::dlog_secp256k1_generic::Secp256k1DlogProof::prove_custom].
+    pub fn prove_custom(secret_keys: &[Scalar], aux_input: &[u8]) -> Result<Self, anyhow::Error> {
+        Self::prove_hedged(secret_keys, aux_input, None)
+    }
+
+    /// Create a zk-STARK proof as with [`prove_custom`][Self::prove_custom], from the typed
+    /// [`AuxInput`][crate::program::Program::AuxInput] of the program `P`.
+    pub fn prove(secret_keys: &[Scalar], aux_input: &P::AuxInput) -> Result<Self, anyhow::Error> {
+        Self::prove_custom(secret_keys, &aux_input.to_bytes())
+    }
+
+    /// Create a zk-STARK proof as with [`prove_custom`][Self::prove_custom], but hedge the
+    /// Schnorr nonces with 32 bytes of randomness drawn from `rng`.
+    pub fn prove_custom_with_rng<R: RngCore + CryptoRng>(
+        secret_keys: &[Scalar],
+        aux_input: &[u8],
+        rng: &mut R,
+    ) -> Result<Self, anyhow::Error> {
+        let mut aux_rand = Zeroizing::new([0u8; 32]);
+        rng.fill_bytes(aux_rand.as_mut_slice());
+        Self::prove_hedged(secret_keys, aux_input, Some(&*aux_rand))
+    }
+
+    fn prove_hedged(
+        secret_keys: &[Scalar],
+        aux_input: &[u8],
+        aux_rand: Option<&[u8; 32]>,
+    ) -> Result<Self, anyhow::Error> {
+        let (guest_input, public_keys, public_nonces) =
+            Self::guest_input(secret_keys, aux_input, aux_rand)?;
+
+        // Stream the input from our own buffer, which we can zeroize afterwards.
+        let env = ExecutorEnv::builder()
+            .stdin(guest_input.as_slice())
+            .build()?;
+
+        // This call takes a while.
+        let receipt = receipt::prove::<P>(env)?;
+
+        Self::from_receipt(public_keys, public_nonces, receipt)
+    }
+
+    /// Construct a proof from a receipt, and the public keys and public nonces which its
+    /// Schnorr signatures are bound to, in order.
+    ///
+    /// Fails if there is not one key and nonce per key of `P`, or if the journal is not a
+    /// valid output of `P`. This does not verify the proof.
+    pub fn from_receipt(
+        public_keys: Vec<Point>,
+        public_nonces: Vec<Point>,
+        receipt: Receipt,
+    ) -> Result<Self, anyhow::Error> {
+        let proof = Secp256k1MultiDlogProof {
+            public_keys,
+            public_nonces,
+            receipt,
+            phantom: PhantomData,
+        };
+        proof.check_lengths()?;
+        Ok(proof)
+    }
+
+    /// Execute the guest program without proving, and return the journal it outputs.
+    #[cfg(test)]
+    pub(crate) fn execute_custom(
+        secret_keys: &[Scalar],
+        aux_input: &[u8],
+    ) -> Result<Vec<u8>, anyhow::Error> {
+        let (guest_input, _, _) = Self::guest_input(secret_keys, aux_input, None)?;
+        let env = ExecutorEnv::builder()
+            .stdin(guest_input.as_slice())
+            .build()?;
+        let session_info = risc0_zkvm::default_executor().execute(env, P::elf())?;
+        Ok(session_info.journal.bytes)
+    }
+
+    /// Return the public keys whose secret keys are inputs to the program, in order.
+    pub fn public_keys(&self) -> &[Point] {
+        &self.public_keys
+    }
+
+    /// Return the public nonces of the Schnorr signatures in the journal, in order.
+    pub fn public_nonces(&self) -> &[Point] {
+        &self.public_nonces
+    }
+
+    /// Return a reference to the receipt of the proof.
+    pub fn receipt(&self) -> &Receipt {
+        &self.receipt
+    }
+
+    /// Consume the proof and return its receipt.
+    pub fn into_receipt(self) -> Receipt {
+        self.receipt
+    }
+
+    /// Return a reference the bytes of the RISC0 guest program output (AKA the journal).
+    pub fn journal(&self) -> &[u8] {
+        &self.receipt.journal.bytes
+    }
+
+    /// Journal:
+    /// - For each key:
+    ///   - Schnorr challenge: 32 bytes
+    ///   - Schnorr sig:       32 bytes
+    /// - Appendix:            P::appendix_len() bytes
+    fn check_lengths(&self) -> Result<(), anyhow::Error> {
+        if self.public_keys.len() != P::key_count() || self.public_nonces.len() != P::key_count() {
+            bail!(
+                "expected {} public keys and nonces; got {} and {}",
+                P::key_count(),
+                self.public_keys.len(),
+                self.public_nonces.len()
+            );
+        }
+
+        let expected = 64 * P::key_count() + P::appendix_len();
+        if self.journal().len() != expected {
+            bail!(VerifyError::JournalLength {
+                actual: self.journal().len(),
+                expected,
+            });
+        }
+        P::check_appendix(self.appendix())
+    }
+
+    /// Return the SHA256 digest of the journal.
+    pub fn journal_digest(&self) -> Digest {
+        self.receipt.journal.digest()
+    }
+
+    /// Return the digest of the claim this proof makes: that the program `P` executed
+    /// successfully and output the journal of this proof.
+    pub fn claim_digest(&self) -> Digest {
+        ReceiptClaim::ok(P::id(), self.journal().to_vec()).digest()
+    }
+
+    /// Parse and return the challenge scalar used to create the `i`th Schnorr signature,
+    /// from the guest output journal. Fails if `i >= P::key_count()`.
+    pub fn challenge(&self, i: usize) -> Result<MaybeScalar, anyhow::Error> {
+        check_key_index::<P>(i)?;
+        let scalar = MaybeScalar::try_from(&self.journal()[i * 64..][..32])
+            .map_err(|_| VerifyError::InvalidScalar)?;
+        Ok(scalar)
+    }
+
+    /// Parse and return the `i`th Schnorr signature scalar `s` from the guest output
+    /// journal. Fails if `i >= P::key_count()`.
+    pub fn signature(&self, i: usize) -> Result<MaybeScalar, anyhow::Error> {
+        check_key_index::<P>(i)?;
+        let scalar = MaybeScalar::try_from(&self.journal()[i * 64 + 32..][..32])
+            .map_err(|_| VerifyError::InvalidScalar)?;
+        Ok(scalar)
+    }
+
+    /// Return a reference to the _appendix,_ which refers to any journal output
+    /// from the guest _after_ the Schnorr signatures.
+    pub fn appendix(&self) -> &[u8] {
+        &self.journal()[64 * P::key_count()..]
+    }
+
+    /// Decode the [`appendix`][Self::appendix] as the typed [`Appendix`][crate::program::Program::Appendix]
+    /// of the program `P`.
+    pub fn appendix_typed(&self) -> Result<P::Appendix, anyhow::Error> {
+        P::Appendix::from_bytes(self.appendix())
+    }
+
+    /// Serialize the proof to a compact vector of bytes, prefixed with the
+    /// [`PROTOCOL_VERSION`] byte.
+    ///
+    /// We use [`borsh`](https://github.com/near/borsh-rs) for binary serialization.
+    pub fn to_vec(&self) -> io::Result<Vec<u8>> {
+        borsh::to_vec(self)
+    }
+
+    /// Deserialize a proof from a vector of bytes.
+    ///
+    /// We use [`borsh`](https://github.com/near/borsh-rs) for binary serialization.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        borsh::from_slice(bytes)
+    }
+
+    /// Verify every Schnorr signature, and then the zk-STARK proof of computational integrity.
+    /// Returns `Ok` if the program `P` was executed correctly AND every secp256k1 Schnorr
+    /// signature is valid under its public key.
+    ///
+    /// Fake receipts produced in dev mode are always rejected.
+    pub fn verify(&self) -> Result<(), anyhow::Error> {
+        self.verify_with_opts(&VerifyOpts::default())
+    }
+
+    /// Verify the proof as with [`verify`][Self::verify], but with custom options.
+    pub fn verify_with_opts(&self, opts: &VerifyOpts) -> Result<(), anyhow::Error> {
+        self.verify_with_allowed_ids_and_opts(&[P::id()], opts)
+    }
+
+    /// Verify the proof as with [`verify`][Self::verify], but accept proofs produced by any
+    /// of the programs in `allowed_ids`, rather than only by `P` as built into this crate.
+    /// See [`Secp256k1DlogProof::verify_with_allowed_ids`][super::dlog_secp256k1_generic::Secp256k1DlogProof::verify_with_allowed_ids].
+    pub fn verify_with_allowed_ids(&self, allowed_ids: &[[u32; 8]]) -> Result<(), anyhow::Error> {
+        self.verify_with_allowed_ids_and_opts(allowed_ids, &VerifyOpts::default())
+    }
+
+    /// Verify the proof as with [`verify_with_allowed_ids`][Self::verify_with_allowed_ids],
+    /// but with custom options.
+    pub fn verify_with_allowed_ids_and_opts(
+        &self,
+        allowed_ids: &[[u32; 8]],
+        opts: &VerifyOpts,
+    ) -> Result<(), anyhow::Error> {
+        // The challenges commit to the image ID, so check it first, to report a proof from
+        // another release as such rather than as a bad signature.
+        let image_id = receipt::check_allowed_image_id(&self.receipt, allowed_ids)?;
+
+        self.check_lengths()?;
+
+        for (i, (&public_key, &public_nonce)) in
+            self.public_keys.iter().zip(&self.public_nonces).enumerate()
+        {
+            let challenge = compute_challenge(image_id, public_nonce, public_key);
+            if challenge != self.challenge(i)? {
+                bail!(VerifyError::ChallengeMismatch);
+            }
+
+            let s = self.signature(i)?;
+            if s * G != public_nonce + public_key * challenge {
+                bail!(VerifyError::InvalidSignature);
+            }
+        }
+
+        receipt::verify_image_id(&self.receipt, image_id, opts)?;
+
+        Ok(())
+    }
+
+    /// Verify the proof, and wrap it in a [`Verified`] marker if it is valid.
+    pub fn into_verified(self) -> Result<Verified<Self>, anyhow::Error> {
+        self.into_verified_with_opts(&VerifyOpts::default())
+    }
+
+    /// Verify the proof with custom options, and wrap it in a [`Verified`] marker if it is valid.
+    pub fn into_verified_with_opts(
+        self,
+        opts: &VerifyOpts,
+    ) -> Result<Verified<Self>, anyhow::Error> {
+        self.verify_with_opts(opts)?;
+        Ok(Verified::new(self))
+    }
+}
+
+fn check_key_index<P: MultiKeyProgram>(i: usize) -> Result<(), anyhow::Error> {
+    if i >= P::key_count() {
+        bail!(
+            "key index {i} out of range for proof of {} keys",
+            P::key_count()
+        );
+    }
+    Ok(())
+}
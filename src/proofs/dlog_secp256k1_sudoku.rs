@@ -1,66 +1,212 @@
 // These constants represent the RISC-V ELF and the image ID generated by risc0-build.
 // The ELF is used for proving and the ID is used for verification.
-use crate::methods::{DLOG_SECP256K1_SUDOKU_ELF, DLOG_SECP256K1_SUDOKU_ID};
+use crate::methods::{
+    DLOG_SECP256K1_SUDOKU_AEAD_ELF, DLOG_SECP256K1_SUDOKU_AEAD_ID,
+    DLOG_SECP256K1_SUDOKU_CIPHER_ELF, DLOG_SECP256K1_SUDOKU_CIPHER_ID, DLOG_SECP256K1_SUDOKU_ELF,
+    DLOG_SECP256K1_SUDOKU_ID, DLOG_SECP256K1_SUDOKU_UNIQUE_ELF, DLOG_SECP256K1_SUDOKU_UNIQUE_ID,
+    DLOG_SECP256K1_SUDOKU_V2_ELF, DLOG_SECP256K1_SUDOKU_V2_ID,
+};
 
 use anyhow::bail;
-use chacha20::{
-    cipher::{KeyIvInit, StreamCipher},
-    ChaCha20,
-};
-use risc0_zkvm::sha::rust_crypto::{Digest as _, Sha256};
+use rand_core::{CryptoRng, RngCore};
+use risc0_zkvm::sha::rust_crypto::Digest as _;
 use risc0_zkvm::sha::Digest;
 use secp::Scalar;
+use zeroize::Zeroizing;
 
 use super::dlog_secp256k1_generic::Secp256k1DlogProof;
+use super::sudoku_cipher;
+use super::sudoku_data::{SudokuAppendix, SudokuAuxInput};
+use crate::hashes;
 use crate::program::Program;
+use common::cipher::{ChaCha20Cipher, GuestCipher};
 use common::sudoku::{self, CompactSudokuBoard, SudokuBoard};
 
-/// This program takes in the following secret inputs:
-///
-/// - `secret_key` (32 bytes)
-/// - `secret_nonce` (32 bytes)
-/// - `challenge` (32 bytes)
-/// - `chacha_nonce` (12 bytes)
-/// - `mask` (81 bytes)
-/// - `sudoku_solution` (81 bytes)
-///
-/// It asserts that `sudoku_solution` is a valid sudoku board, and then
-/// produces the following public outputs:
-///
-/// - `challenge` (32 bytes)
-/// - `sig_scalar = (secret_key * challenge + secret_nonce) % n` (32 bytes)
-/// - `chacha_nonce` (12 bytes)
-/// - `sudoku_puzzle = mask_sudoku_solution(sudoku_solution, mask)` (81 bytes)
-/// - `compact_encrypted_solution = chacha_cipher(secret_key).encrypt(compress_board(sudoku_solution))` (36 bytes)
-///
-/// This program is used to instantiate [`Secp256k1DlogSudokuProof`].
-#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash)]
-pub struct Secp256k1DlogSudokuProgram;
-
-impl Program for Secp256k1DlogSudokuProgram {
-    fn id() -> [u32; 8] {
-        DLOG_SECP256K1_SUDOKU_ID
-    }
-    fn elf() -> &'static [u8] {
-        DLOG_SECP256K1_SUDOKU_ELF
-    }
-
-    // aux input:
-    // - chacha nonce: 12 bytes
-    // - sudoku puzzle mask: 81 bytes
-    // - sudoku solution: 81 bytes
-    fn aux_input_len() -> usize {
-        12 + 81 + 81
-    }
-
-    /// Journal:
-    /// - schnorr challenge: 32 bytes
-    /// - schnorr sig: 32 bytes
-    /// - chacha nonce: 12 bytes
-    /// - sudoku puzzle: 81 bytes
-    /// - encrypted compact sudoku solution: 36 bytes
-    fn appendix_len() -> usize {
-        12 + 81 + 36
+crate::zkcp_program! {
+    /// This program takes in the following secret inputs:
+    ///
+    /// - `secret_key` (32 bytes)
+    /// - `secret_nonce` (32 bytes)
+    /// - `challenge` (32 bytes)
+    /// - `chacha_nonce` (12 bytes)
+    /// - `mask` (81 bytes)
+    /// - `sudoku_solution` (81 bytes)
+    ///
+    /// It asserts that `sudoku_solution` is a valid sudoku board, and then
+    /// produces the following public outputs:
+    ///
+    /// - `challenge` (32 bytes)
+    /// - `sig_scalar = (secret_key * challenge + secret_nonce) % n` (32 bytes)
+    /// - `chacha_nonce` (12 bytes)
+    /// - `sudoku_puzzle = mask_sudoku_solution(sudoku_solution, mask)` (81 bytes)
+    /// - `compact_encrypted_solution = chacha_cipher(secret_key).encrypt(compress_board(sudoku_solution))` (36 bytes)
+    ///
+    /// This program is used to instantiate [`Secp256k1DlogSudokuProof`].
+    ///
+    /// This program uses the secret key verbatim as the chacha20 key. It is kept for compatibility;
+    /// new applications should use [`Secp256k1DlogSudokuV2Program`].
+    pub struct Secp256k1DlogSudokuProgram {
+        elf: DLOG_SECP256K1_SUDOKU_ELF,
+        id: DLOG_SECP256K1_SUDOKU_ID,
+        aux_input: SudokuAuxInput,
+        appendix: SudokuAppendix,
+    }
+}
+
+crate::zkcp_program! {
+    /// Identical to [`Secp256k1DlogSudokuProgram`], except that the chacha20 key is derived from
+    /// the secret key with the tagged hash defined in [`common::kdf`]:
+    ///
+    /// - `compact_encrypted_solution = chacha_cipher(kdf(secret_key)).encrypt(compress_board(sudoku_solution))` (36 bytes)
+    ///
+    /// This ensures a secret key which is also used as a Bitcoin key is never used verbatim as a
+    /// symmetric key. This program is used to instantiate [`Secp256k1DlogSudokuV2Proof`].
+    pub struct Secp256k1DlogSudokuV2Program {
+        elf: DLOG_SECP256K1_SUDOKU_V2_ELF,
+        id: DLOG_SECP256K1_SUDOKU_V2_ID,
+        aux_input: SudokuAuxInput,
+        appendix: SudokuAppendix,
+    }
+}
+
+crate::zkcp_program! {
+    /// Identical to [`Secp256k1DlogSudokuV2Program`], except that the guest also asserts that
+    /// the puzzle has exactly one solution, using the solver in [`common::solver`]. The inputs
+    /// and journal are the same.
+    ///
+    /// Without this, a proof only asserts that the encrypted board solves the published puzzle.
+    /// If the puzzle has several solutions, the seller could sell any one of them. Proving
+    /// fails outright if the puzzle is not unique. Buyers who only need assurance before
+    /// purchase can instead check the puzzle of any sudoku proof with
+    /// `puzzle_has_unique_solution`, which requires the `solver` feature. This program is used
+    /// to instantiate [`Secp256k1DlogSudokuUniqueProof`].
+    ///
+    /// # Cycle cost
+    ///
+    /// The other sudoku guests check the solution in a single pass over the board, so their
+    /// cost is fixed and dominated by the cipher. This guest additionally runs a dancing links
+    /// (DLX) search in the zkVM, which must exhaust the whole search tree to rule out a second
+    /// solution. Its cost therefore depends on the puzzle: puzzles with few clues leave more
+    /// of the tree to search, and near the 17-clue minimum the search can take many times the
+    /// cycles of the rest of the guest, and so many times the proving time. Measure
+    /// representative puzzles before relying on this program, for example by executing it
+    /// with `RISC0_INFO=1` set, which logs the cycle count of each execution.
+    pub struct Secp256k1DlogSudokuUniqueProgram {
+        elf: DLOG_SECP256K1_SUDOKU_UNIQUE_ELF,
+        id: DLOG_SECP256K1_SUDOKU_UNIQUE_ID,
+        aux_input: SudokuAuxInput,
+        appendix: SudokuAppendix,
+    }
+}
+
+crate::zkcp_program! {
+    /// Identical to [`Secp256k1DlogSudokuV2Program`], except that the solution is encrypted with
+    /// chacha20poly1305, authenticating the puzzle as associated data, and the tag is committed
+    /// at the end of the journal:
+    ///
+    /// - `compact_encrypted_solution = chacha20poly1305(kdf(secret_key)).encrypt(compress_board(sudoku_solution), aad = sudoku_puzzle)` (36 bytes)
+    /// - `tag` (16 bytes)
+    ///
+    /// Corrupted ciphertexts are then rejected on decryption, instead of decrypting to garbage.
+    /// This program is used to instantiate [`Secp256k1DlogSudokuAeadProof`].
+    pub struct Secp256k1DlogSudokuAeadProgram {
+        elf: DLOG_SECP256K1_SUDOKU_AEAD_ELF,
+        id: DLOG_SECP256K1_SUDOKU_AEAD_ID,
+        aux_input: SudokuAuxInput,
+        // The poly1305 tag follows the usual appendix.
+        appendix: (SudokuAppendix, [u8; 16]),
+    }
+}
+
+crate::zkcp_program! {
+    /// Identical to [`Secp256k1DlogSudokuV2Program`], except that the prover chooses the cipher
+    /// which encrypts the solution. The cipher ID is appended to the aux input, and committed at
+    /// the end of the journal:
+    ///
+    /// - `compact_encrypted_solution = C(kdf(secret_key)).encrypt(compress_board(sudoku_solution))` (36 bytes)
+    /// - `cipher_id = C::ID` (1 byte)
+    ///
+    /// All ciphers share the same guest program and image ID, so the host checks that the cipher
+    /// ID in the journal is that of `C` whenever a proof is constructed, deserialized or
+    /// verified. This program is used to instantiate
+    /// [`Secp256k1DlogSudokuCipherProof`].
+    pub struct Secp256k1DlogSudokuCipherProgram<C: GuestCipher> {
+        elf: DLOG_SECP256K1_SUDOKU_CIPHER_ELF,
+        id: DLOG_SECP256K1_SUDOKU_CIPHER_ID,
+        // The cipher ID follows the usual aux input and appendix.
+        aux_input: (SudokuAuxInput, u8),
+        appendix: (SudokuAppendix, u8),
+        check_appendix: sudoku_cipher::check_cipher_id::<C>,
+    }
+}
+
+/// A version of the secp256k1-dlog-sudoku program. All versions share the same input layout,
+/// and their journals begin with the same fields, but they differ in how the secret key is
+/// turned into a cipher key and how the solution is encrypted.
+pub trait Secp256k1DlogSudokuVersion: Program {
+    /// Derive the key which the guest uses to encrypt the solution.
+    fn cipher_key(secret_key: Scalar) -> Zeroizing<[u8; 32]>;
+
+    /// The cipher ID appended to the aux input, for programs which let the prover choose
+    /// a cipher.
+    fn cipher_id() -> Option<u8> {
+        None
+    }
+
+    /// Decrypt the compact solution from the appendix of the journal.
+    fn decrypt_compact_solution(
+        cipher_key: &[u8; 32],
+        appendix: &[u8],
+    ) -> Result<Zeroizing<CompactSudokuBoard>, anyhow::Error> {
+        sudoku_cipher::decrypt_stream::<ChaCha20Cipher>(cipher_key, appendix)
+    }
+}
+
+impl Secp256k1DlogSudokuVersion for Secp256k1DlogSudokuProgram {
+    fn cipher_key(secret_key: Scalar) -> Zeroizing<[u8; 32]> {
+        Zeroizing::new(secret_key.serialize())
+    }
+}
+
+impl Secp256k1DlogSudokuVersion for Secp256k1DlogSudokuV2Program {
+    fn cipher_key(secret_key: Scalar) -> Zeroizing<[u8; 32]> {
+        hashes::derive_cipher_key(&Zeroizing::new(secret_key.serialize()))
+    }
+}
+
+impl Secp256k1DlogSudokuVersion for Secp256k1DlogSudokuUniqueProgram {
+    fn cipher_key(secret_key: Scalar) -> Zeroizing<[u8; 32]> {
+        hashes::derive_cipher_key(&Zeroizing::new(secret_key.serialize()))
+    }
+}
+
+impl Secp256k1DlogSudokuVersion for Secp256k1DlogSudokuAeadProgram {
+    fn cipher_key(secret_key: Scalar) -> Zeroizing<[u8; 32]> {
+        hashes::derive_cipher_key(&Zeroizing::new(secret_key.serialize()))
+    }
+
+    fn decrypt_compact_solution(
+        cipher_key: &[u8; 32],
+        appendix: &[u8],
+    ) -> Result<Zeroizing<CompactSudokuBoard>, anyhow::Error> {
+        sudoku_cipher::decrypt_chacha20_poly1305(cipher_key, appendix)
+    }
+}
+
+impl<C: GuestCipher> Secp256k1DlogSudokuVersion for Secp256k1DlogSudokuCipherProgram<C> {
+    fn cipher_key(secret_key: Scalar) -> Zeroizing<[u8; 32]> {
+        hashes::derive_cipher_key(&Zeroizing::new(secret_key.serialize()))
+    }
+
+    fn cipher_id() -> Option<u8> {
+        Some(C::ID)
+    }
+
+    fn decrypt_compact_solution(
+        cipher_key: &[u8; 32],
+        appendix: &[u8],
+    ) -> Result<Zeroizing<CompactSudokuBoard>, anyhow::Error> {
+        sudoku_cipher::decrypt_stream_with_id::<C>(cipher_key, appendix)
     }
 }
 
@@ -68,31 +214,88 @@ impl Program for Secp256k1DlogSudokuProgram {
 /// sudoku solution.
 pub type Secp256k1DlogSudokuProof = Secp256k1DlogProof<Secp256k1DlogSudokuProgram>;
 
-impl Secp256k1DlogSudokuProof {
+/// A proof that the secret key of a secp256k1 public key can be used to derive the decryption
+/// key to a valid sudoku solution.
+pub type Secp256k1DlogSudokuV2Proof = Secp256k1DlogProof<Secp256k1DlogSudokuV2Program>;
+
+/// A proof that the secret key of a secp256k1 public key can be used to derive the decryption
+/// key to the unique solution of a sudoku puzzle.
+pub type Secp256k1DlogSudokuUniqueProof = Secp256k1DlogProof<Secp256k1DlogSudokuUniqueProgram>;
+
+/// A proof that the secret key of a secp256k1 public key can be used to derive the key
+/// which decrypts and authenticates a valid sudoku solution.
+pub type Secp256k1DlogSudokuAeadProof = Secp256k1DlogProof<Secp256k1DlogSudokuAeadProgram>;
+
+/// A proof that the secret key of a secp256k1 public key can be used to derive the key
+/// to a valid sudoku solution encrypted with the cipher `C`.
+pub type Secp256k1DlogSudokuCipherProof<C> =
+    Secp256k1DlogProof<Secp256k1DlogSudokuCipherProgram<C>>;
+
+/// Construct the auxiliary input to a [`Secp256k1DlogSudokuVersion`] program. The cipher nonce
+/// is derived deterministically from the inputs, hedged with `aux_rand` if given.
+fn sudoku_aux_input<P: Secp256k1DlogSudokuVersion>(
+    secret_key: Scalar,
+    solution: &SudokuBoard,
+    puzzle_mask: &SudokuBoard,
+    aux_rand: Option<&[u8; 32]>,
+) -> Zeroizing<Vec<u8>> {
+    let mut hasher = hashes::tagged_hasher(hashes::CHACHA_NONCE_TAG)
+        .chain_update(Digest::from(P::id()))
+        .chain_update(Zeroizing::new(secret_key.serialize()).as_slice())
+        .chain_update(solution)
+        .chain_update(puzzle_mask);
+    if let Some(cipher_id) = P::cipher_id() {
+        hasher.update([cipher_id]);
+    }
+    if let Some(aux_rand) = aux_rand {
+        hasher.update(aux_rand);
+    }
+    let cipher_nonce_hash = hasher.finalize();
+
+    let mut aux_input = Zeroizing::new(Vec::with_capacity(P::aux_input_len()));
+    aux_input.extend_from_slice(&cipher_nonce_hash[..12]);
+    aux_input.extend_from_slice(puzzle_mask);
+    aux_input.extend_from_slice(solution);
+    aux_input.extend(P::cipher_id());
+    aux_input
+}
+
+impl<P: Secp256k1DlogSudokuVersion> Secp256k1DlogProof<P> {
     pub fn new(
         secret_key: Scalar,
         solution: &SudokuBoard,
         puzzle_mask: &SudokuBoard,
     ) -> Result<Self, anyhow::Error> {
-        let chacha_nonce_hash = Sha256::new()
-            .chain_update(Digest::from(DLOG_SECP256K1_SUDOKU_ID))
-            .chain_update(secret_key.serialize())
-            .chain_update(solution)
-            .chain_update(puzzle_mask)
-            .chain_update(b"chacha_nonce")
-            .finalize();
-
-        let mut aux_input = [0u8; 12 + 81 + 81];
-        aux_input[..12].copy_from_slice(&chacha_nonce_hash[..12]);
-        aux_input[12..][..81].copy_from_slice(puzzle_mask);
-        aux_input[12..][81..].copy_from_slice(solution);
+        let aux_input = sudoku_aux_input::<P>(secret_key, solution, puzzle_mask, None);
+        Self::prove_custom(secret_key, aux_input.as_slice())
+    }
 
-        Self::prove_custom(secret_key, &aux_input)
+    /// Create a proof as with [`new`][Self::new], but hedge both the Schnorr nonce and the
+    /// cipher nonce with randomness drawn from `rng`. See
+    /// [`prove_custom_with_rng`][Self::prove_custom_with_rng].
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        secret_key: Scalar,
+        solution: &SudokuBoard,
+        puzzle_mask: &SudokuBoard,
+        rng: &mut R,
+    ) -> Result<Self, anyhow::Error> {
+        let mut aux_rand = Zeroizing::new([0u8; 32]);
+        rng.fill_bytes(aux_rand.as_mut_slice());
+        let aux_input = sudoku_aux_input::<P>(secret_key, solution, puzzle_mask, Some(&*aux_rand));
+        Self::prove_custom_with_rng(secret_key, aux_input.as_slice(), rng)
     }
 
     pub fn puzzle(&self) -> SudokuBoard {
         SudokuBoard::try_from(&self.journal()[64..][12..][..81])
-            .expect("journal length already checked in constructor")
+            .expect("journal length checked on construction and deserialization")
+    }
+
+    /// Returns true if the puzzle has exactly one solution. Buyers should check this before
+    /// purchase, unless the program already asserts it, as
+    /// [`Secp256k1DlogSudokuUniqueProgram`] does.
+    #[cfg(feature = "solver")]
+    pub fn puzzle_has_unique_solution(&self) -> bool {
+        common::solver::has_unique_solution(&self.puzzle())
     }
 
     pub fn decrypt_solution(&self, secret_key: Scalar) -> Result<SudokuBoard, anyhow::Error> {
@@ -100,14 +303,8 @@ impl Secp256k1DlogSudokuProof {
             bail!("secret key does not match public key used in discrete log proof");
         }
 
-        let chacha_key: [u8; 32] = secret_key.serialize();
-        let chacha_nonce =
-            <[u8; 12]>::try_from(&self.journal()[64..][..12]).expect("always correct length");
-
-        let mut compact_solution = CompactSudokuBoard::try_from(&self.journal()[64..][12..][81..])
-            .expect("always correct length");
-        let mut cipher = ChaCha20::new(&chacha_key.into(), &chacha_nonce.into());
-        cipher.apply_keystream(&mut compact_solution);
+        let cipher_key = P::cipher_key(secret_key);
+        let compact_solution = P::decrypt_compact_solution(&cipher_key, self.appendix())?;
 
         let solution = sudoku::decompress_board(&compact_solution)?;
 
@@ -125,3 +322,66 @@ impl Secp256k1DlogSudokuProof {
         Ok(solution)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::GuestData;
+    use common::test_fixtures::{MASK, SOLUTION};
+
+    #[test]
+    fn test_deterministic_journal() {
+        let secret_key = Scalar::reduce_from(&[3u8; 32]);
+
+        let aux_input =
+            sudoku_aux_input::<Secp256k1DlogSudokuProgram>(secret_key, &SOLUTION, &MASK, None);
+        assert_eq!(
+            aux_input,
+            sudoku_aux_input::<Secp256k1DlogSudokuProgram>(secret_key, &SOLUTION, &MASK, None)
+        );
+        assert_ne!(
+            aux_input[..12],
+            sudoku_aux_input::<Secp256k1DlogSudokuProgram>(
+                secret_key,
+                &SOLUTION,
+                &MASK,
+                Some(&[1; 32])
+            )[..12]
+        );
+
+        let journal1 =
+            Secp256k1DlogSudokuProof::execute_custom(secret_key, aux_input.as_slice()).unwrap();
+        let journal2 =
+            Secp256k1DlogSudokuProof::execute_custom(secret_key, aux_input.as_slice()).unwrap();
+        assert_eq!(journal1, journal2);
+        assert_eq!(
+            &journal1[64..][12..][..81],
+            &sudoku::mask_sudoku_solution(&SOLUTION, &MASK)
+        );
+    }
+
+    #[test]
+    fn test_typed_aux_input_and_appendix() {
+        type CipherProgram = Secp256k1DlogSudokuCipherProgram<ChaCha20Cipher>;
+        let secret_key = Scalar::reduce_from(&[3u8; 32]);
+
+        let aux_input = sudoku_aux_input::<CipherProgram>(secret_key, &SOLUTION, &MASK, None);
+        let (typed_aux_input, cipher_id) =
+            <CipherProgram as Program>::AuxInput::from_bytes(&aux_input).unwrap();
+        assert_eq!(typed_aux_input.mask, MASK);
+        assert_eq!(typed_aux_input.solution.expose_secret(), &SOLUTION);
+        assert_eq!(Some(cipher_id), CipherProgram::cipher_id());
+        assert_eq!((typed_aux_input.clone(), cipher_id).to_bytes(), aux_input);
+
+        let journal =
+            Secp256k1DlogProof::<CipherProgram>::execute_custom(secret_key, &aux_input).unwrap();
+        let (appendix, cipher_id) =
+            <CipherProgram as Program>::Appendix::from_bytes(&journal[64..]).unwrap();
+        assert_eq!(appendix.cipher_nonce, typed_aux_input.cipher_nonce);
+        assert_eq!(
+            appendix.puzzle,
+            sudoku::mask_sudoku_solution(&SOLUTION, &MASK)
+        );
+        assert_eq!(Some(cipher_id), CipherProgram::cipher_id());
+    }
+}
@@ -0,0 +1,254 @@
+// These constants represent the RISC-V ELF and the image ID generated by risc0-build.
+// The ELF is used for proving and the ID is used for verification.
+use crate::methods::{DLOG_SECP256K1_SUDOKU_BATCH_ELF, DLOG_SECP256K1_SUDOKU_BATCH_ID};
+
+use anyhow::bail;
+use rand_core::{CryptoRng, RngCore};
+use risc0_zkvm::sha::rust_crypto::Digest as _;
+use risc0_zkvm::sha::Digest;
+use secp::Scalar;
+use zeroize::Zeroizing;
+
+use super::dlog_secp256k1_generic::Secp256k1DlogProof;
+use super::sudoku_data::{SudokuBatchAppendix, SudokuBatchAuxInput};
+use crate::hashes;
+use crate::program::Program;
+use common::cipher::ChaCha20Cipher;
+use common::sudoku::{self, CompactSudokuBoard, SudokuBoard};
+
+crate::zkcp_program! {
+    /// This program takes in the following secret inputs:
+    ///
+    /// - `secret_key` (32 bytes)
+    /// - `secret_nonce` (32 bytes)
+    /// - `challenge` (32 bytes)
+    /// - `count = N` (4 bytes, big-endian)
+    /// - `chacha_nonce` (12 bytes)
+    /// - `N` pairs of:
+    ///   - `mask` (81 bytes)
+    ///   - `sudoku_solution` (81 bytes)
+    ///
+    /// It asserts that every `sudoku_solution` is a valid sudoku board, and then
+    /// produces the following public outputs:
+    ///
+    /// - `challenge` (32 bytes)
+    /// - `sig_scalar = (secret_key * challenge + secret_nonce) % n` (32 bytes)
+    /// - `count = N` (4 bytes, big-endian)
+    /// - `chacha_nonce` (12 bytes)
+    /// - `sudoku_puzzles = [mask_sudoku_solution(sudoku_solution, mask); N]` (N * 81 bytes)
+    /// - `compact_encrypted_solutions = chacha_cipher(kdf(secret_key)).encrypt([compress_board(sudoku_solution); N])` (N * 36 bytes)
+    ///
+    /// All solutions are encrypted as one continuous chacha20 keystream. The guest program
+    /// accepts any `N`, but the journal length depends on it, so the batch size is fixed
+    /// at the type level. This program is used to instantiate [`Secp256k1DlogSudokuBatchProof`].
+    pub struct Secp256k1DlogSudokuBatchProgram<const N: usize> {
+        elf: DLOG_SECP256K1_SUDOKU_BATCH_ELF,
+        id: DLOG_SECP256K1_SUDOKU_BATCH_ID,
+        aux_input: SudokuBatchAuxInput<N>,
+        appendix: SudokuBatchAppendix<N>,
+    }
+
+    /// A proof that the secret key of a secp256k1 public key can be used to derive the
+    /// decryption key to valid solutions for each of `N` sudoku puzzles.
+    pub type Secp256k1DlogSudokuBatchProof = Secp256k1DlogProof<Self>;
+}
+
+/// Construct the auxiliary input to [`Secp256k1DlogSudokuBatchProgram`]. The chacha nonce is
+/// derived deterministically from the inputs, hedged with `aux_rand` if given.
+fn batch_aux_input<const N: usize>(
+    secret_key: Scalar,
+    solutions: &[SudokuBoard; N],
+    puzzle_masks: &[SudokuBoard; N],
+    aux_rand: Option<&[u8; 32]>,
+) -> Zeroizing<Vec<u8>> {
+    let count = (N as u32).to_be_bytes();
+
+    let mut hasher = hashes::tagged_hasher(hashes::CHACHA_NONCE_TAG)
+        .chain_update(Digest::from(Secp256k1DlogSudokuBatchProgram::<N>::id()))
+        .chain_update(Zeroizing::new(secret_key.serialize()).as_slice())
+        .chain_update(count);
+    for (solution, puzzle_mask) in solutions.iter().zip(puzzle_masks) {
+        hasher.update(solution);
+        hasher.update(puzzle_mask);
+    }
+    if let Some(aux_rand) = aux_rand {
+        hasher.update(aux_rand);
+    }
+    let chacha_nonce_hash = hasher.finalize();
+
+    let mut aux_input = Zeroizing::new(Vec::with_capacity(
+        Secp256k1DlogSudokuBatchProgram::<N>::aux_input_len(),
+    ));
+    aux_input.extend_from_slice(&count);
+    aux_input.extend_from_slice(&chacha_nonce_hash[..12]);
+    for (solution, puzzle_mask) in solutions.iter().zip(puzzle_masks) {
+        aux_input.extend_from_slice(puzzle_mask);
+        aux_input.extend_from_slice(solution);
+    }
+    aux_input
+}
+
+impl<const N: usize> Secp256k1DlogSudokuBatchProof<N> {
+    /// Prove that `secret_key` decrypts the solution to each of the puzzles given by
+    /// masking `solutions[i]` with `puzzle_masks[i]`, all in a single receipt.
+    pub fn new(
+        secret_key: Scalar,
+        solutions: &[SudokuBoard; N],
+        puzzle_masks: &[SudokuBoard; N],
+    ) -> Result<Self, anyhow::Error> {
+        if N == 0 {
+            bail!("cannot prove an empty batch of sudoku solutions");
+        }
+        let aux_input = batch_aux_input(secret_key, solutions, puzzle_masks, None);
+        Self::prove_custom(secret_key, aux_input.as_slice())
+    }
+
+    /// Create a proof as with [`new`][Self::new], but hedge both the Schnorr nonce and the
+    /// chacha nonce with randomness drawn from `rng`. See
+    /// [`prove_custom_with_rng`][Self::prove_custom_with_rng].
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        secret_key: Scalar,
+        solutions: &[SudokuBoard; N],
+        puzzle_masks: &[SudokuBoard; N],
+        rng: &mut R,
+    ) -> Result<Self, anyhow::Error> {
+        if N == 0 {
+            bail!("cannot prove an empty batch of sudoku solutions");
+        }
+        let mut aux_rand = Zeroizing::new([0u8; 32]);
+        rng.fill_bytes(aux_rand.as_mut_slice());
+        let aux_input = batch_aux_input(secret_key, solutions, puzzle_masks, Some(&*aux_rand));
+        Self::prove_custom_with_rng(secret_key, aux_input.as_slice(), rng)
+    }
+
+    /// Returns the number of puzzles in the batch.
+    pub fn len(&self) -> usize {
+        N
+    }
+
+    /// Returns true if the batch contains no puzzles.
+    pub fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Returns the `i`th puzzle in the batch, or `None` if `i >= N`.
+    pub fn puzzle(&self, i: usize) -> Option<SudokuBoard> {
+        if i >= N {
+            return None;
+        }
+        let puzzle = SudokuBoard::try_from(&self.appendix()[4 + 12..][i * 81..][..81])
+            .expect("journal length checked on construction and deserialization");
+        Some(puzzle)
+    }
+
+    /// Returns every puzzle in the batch, in order.
+    pub fn puzzles(&self) -> Vec<SudokuBoard> {
+        (0..N).filter_map(|i| self.puzzle(i)).collect()
+    }
+
+    /// Decrypt the solutions to every puzzle in the batch, in order.
+    pub fn decrypt_solutions(&self, secret_key: Scalar) -> Result<Vec<SudokuBoard>, anyhow::Error> {
+        let (cipher_key, chacha_nonce) = self.cipher_key_and_nonce(secret_key)?;
+        (0..N)
+            .map(|i| self.decrypt_item(i, &cipher_key, &chacha_nonce))
+            .collect()
+    }
+
+    /// Decrypt the solution to the `i`th puzzle in the batch. Only that solution is
+    /// decrypted and checked, so the other items in the batch are never touched.
+    pub fn decrypt_solution(
+        &self,
+        i: usize,
+        secret_key: Scalar,
+    ) -> Result<SudokuBoard, anyhow::Error> {
+        if i >= N {
+            bail!("puzzle index {i} out of range for batch of {N}");
+        }
+        let (cipher_key, chacha_nonce) = self.cipher_key_and_nonce(secret_key)?;
+        self.decrypt_item(i, &cipher_key, &chacha_nonce)
+    }
+
+    fn cipher_key_and_nonce(
+        &self,
+        secret_key: Scalar,
+    ) -> Result<(Zeroizing<[u8; 32]>, [u8; 12]), anyhow::Error> {
+        if secret_key.base_point_mul() != self.public_key {
+            bail!("secret key does not match public key used in discrete log proof");
+        }
+
+        let cipher_key = hashes::derive_cipher_key(&Zeroizing::new(secret_key.serialize()));
+        let chacha_nonce = <[u8; 12]>::try_from(&self.appendix()[4..][..12])
+            .expect("journal length checked on construction and deserialization");
+        Ok((cipher_key, chacha_nonce))
+    }
+
+    /// Decrypt and check the `i`th solution. All solutions share one keystream, so the
+    /// `i`th starts `i * 36` bytes into it.
+    fn decrypt_item(
+        &self,
+        i: usize,
+        cipher_key: &[u8; 32],
+        chacha_nonce: &[u8; 12],
+    ) -> Result<SudokuBoard, anyhow::Error> {
+        let mut compact_solution = Zeroizing::new(
+            CompactSudokuBoard::try_from(&self.appendix()[4 + 12 + N * 81..][i * 36..][..36])
+                .expect("journal length checked on construction and deserialization"),
+        );
+        ChaCha20Cipher::apply_keystream_at(
+            cipher_key,
+            chacha_nonce,
+            (i * 36) as u64,
+            compact_solution.as_mut_slice(),
+        );
+        let solution = sudoku::decompress_board(&compact_solution)?;
+
+        if !sudoku::is_valid_sudoku_solution(&solution) {
+            bail!(
+                "decrypted solution {i} is not valid. This should never happen; \
+                   did you forget to verify the proof?"
+            );
+        } else if !sudoku::solves_sudoku_puzzle(&solution, &self.puzzle(i).expect("i < N")) {
+            bail!(
+                "decrypted solution {i} is for the wrong puzzle. This should never happen; \
+                   did you forget to verify the proof?"
+            );
+        }
+        Ok(solution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::test_fixtures::SOLUTION;
+
+    #[test]
+    fn test_batch_journal() {
+        let secret_key = Scalar::reduce_from(&[3u8; 32]);
+        let solutions = [SOLUTION; 3];
+        let mut masks = [[1u8; 81]; 3];
+        masks[1][..40].fill(0);
+        masks[2][40..].fill(0);
+
+        let aux_input = batch_aux_input(secret_key, &solutions, &masks, None);
+        assert_eq!(
+            aux_input.len(),
+            Secp256k1DlogSudokuBatchProgram::<3>::aux_input_len()
+        );
+
+        let journal =
+            Secp256k1DlogSudokuBatchProof::<3>::execute_custom(secret_key, aux_input.as_slice())
+                .unwrap();
+        assert_eq!(
+            journal.len(),
+            64 + Secp256k1DlogSudokuBatchProgram::<3>::appendix_len()
+        );
+        assert_eq!(&journal[64..][..4], &3u32.to_be_bytes());
+        for (i, mask) in masks.iter().enumerate() {
+            assert_eq!(
+                &journal[64 + 4 + 12..][i * 81..][..81],
+                &sudoku::mask_sudoku_solution(&SOLUTION, mask)
+            );
+        }
+    }
+}
@@ -0,0 +1,235 @@
+// These constants represent the RISC-V ELF and the image ID generated by risc0-build.
+// The ELF is used for proving and the ID is used for verification.
+use crate::methods::{DLOG_SECP256K1_SUDOKU_MULTIKEY_ELF, DLOG_SECP256K1_SUDOKU_MULTIKEY_ID};
+
+use anyhow::bail;
+use rand_core::{CryptoRng, RngCore};
+use risc0_zkvm::sha::rust_crypto::Digest as _;
+use risc0_zkvm::sha::Digest;
+use secp::Scalar;
+use zeroize::Zeroizing;
+
+use super::dlog_secp256k1_multi_generic::Secp256k1MultiDlogProof;
+use super::sudoku_cipher;
+use super::sudoku_data::{SudokuAppendix, SudokuAuxInput};
+use crate::hashes;
+use crate::program::Program;
+use common::cipher::ChaCha20Cipher;
+use common::sudoku::{self, SudokuBoard};
+
+crate::zkcp_program! {
+    /// This program takes in the following secret inputs:
+    ///
+    /// - `count = N` (4 bytes, big-endian)
+    /// - `N` triples of:
+    ///   - `secret_key` (32 bytes)
+    ///   - `secret_nonce` (32 bytes)
+    ///   - `challenge` (32 bytes)
+    /// - `N` triples of:
+    ///   - `chacha_nonce` (12 bytes)
+    ///   - `mask` (81 bytes)
+    ///   - `sudoku_solution` (81 bytes)
+    ///
+    /// It asserts that every `sudoku_solution` is a valid sudoku board, and then
+    /// produces the following public outputs:
+    ///
+    /// - `N` pairs of:
+    ///   - `challenge` (32 bytes)
+    ///   - `sig_scalar = (secret_key * challenge + secret_nonce) % n` (32 bytes)
+    /// - `N` triples of:
+    ///   - `chacha_nonce` (12 bytes)
+    ///   - `sudoku_puzzle = mask_sudoku_solution(sudoku_solution, mask)` (81 bytes)
+    ///   - `compact_encrypted_solution = chacha_cipher(kdf(secret_key)).encrypt(compress_board(sudoku_solution))` (36 bytes)
+    ///
+    /// The `i`th solution is encrypted under a key derived from the `i`th secret key, so
+    /// each item can only be decrypted by the owner of the matching public key. This program
+    /// is used to instantiate [`Secp256k1DlogSudokuMultiKeyProof`].
+    pub struct Secp256k1DlogSudokuMultiKeyProgram<const N: usize> {
+        elf: DLOG_SECP256K1_SUDOKU_MULTIKEY_ELF,
+        id: DLOG_SECP256K1_SUDOKU_MULTIKEY_ID,
+        aux_input: [SudokuAuxInput; N],
+        appendix: [SudokuAppendix; N],
+        key_count: N,
+    }
+
+    /// A proof that, for each of `N` secp256k1 public keys, the corresponding secret key can
+    /// be used to derive the decryption key to a valid solution of its own sudoku puzzle.
+    pub type Secp256k1DlogSudokuMultiKeyProof = Secp256k1MultiDlogProof<Self>;
+}
+
+/// Length of each item in the appendix of [`Secp256k1DlogSudokuMultiKeyProgram`].
+const ITEM_LEN: usize = 12 + 81 + 36;
+
+/// Construct the auxiliary input to [`Secp256k1DlogSudokuMultiKeyProgram`]. Each chacha
+/// nonce is derived deterministically from the item's own secret key, index and board,
+/// hedged with `aux_rand` if given.
+fn multikey_aux_input<const N: usize>(
+    secret_keys: &[Scalar; N],
+    solutions: &[SudokuBoard; N],
+    puzzle_masks: &[SudokuBoard; N],
+    aux_rand: Option<&[u8; 32]>,
+) -> Zeroizing<Vec<u8>> {
+    let mut aux_input = Zeroizing::new(Vec::with_capacity(
+        Secp256k1DlogSudokuMultiKeyProgram::<N>::aux_input_len(),
+    ));
+    for (i, ((secret_key, solution), puzzle_mask)) in secret_keys
+        .iter()
+        .zip(solutions)
+        .zip(puzzle_masks)
+        .enumerate()
+    {
+        let mut hasher = hashes::tagged_hasher(hashes::CHACHA_NONCE_TAG)
+            .chain_update(Digest::from(Secp256k1DlogSudokuMultiKeyProgram::<N>::id()))
+            .chain_update(Zeroizing::new(secret_key.serialize()).as_slice())
+            .chain_update((i as u32).to_be_bytes())
+            .chain_update(solution)
+            .chain_update(puzzle_mask);
+        if let Some(aux_rand) = aux_rand {
+            hasher.update(aux_rand);
+        }
+        let chacha_nonce_hash = hasher.finalize();
+
+        aux_input.extend_from_slice(&chacha_nonce_hash[..12]);
+        aux_input.extend_from_slice(puzzle_mask);
+        aux_input.extend_from_slice(solution);
+    }
+    aux_input
+}
+
+impl<const N: usize> Secp256k1DlogSudokuMultiKeyProof<N> {
+    /// Prove that each `secret_keys[i]` decrypts the solution to the puzzle given by
+    /// masking `solutions[i]` with `puzzle_masks[i]`, all in a single receipt.
+    pub fn new(
+        secret_keys: &[Scalar; N],
+        solutions: &[SudokuBoard; N],
+        puzzle_masks: &[SudokuBoard; N],
+    ) -> Result<Self, anyhow::Error> {
+        if N == 0 {
+            bail!("cannot prove an empty set of sudoku solutions");
+        }
+        let aux_input = multikey_aux_input(secret_keys, solutions, puzzle_masks, None);
+        Self::prove_custom(secret_keys, aux_input.as_slice())
+    }
+
+    /// Create a proof as with [`new`][Self::new], but hedge both the Schnorr nonces and the
+    /// chacha nonces with randomness drawn from `rng`. See
+    /// [`prove_custom_with_rng`][Self::prove_custom_with_rng].
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        secret_keys: &[Scalar; N],
+        solutions: &[SudokuBoard; N],
+        puzzle_masks: &[SudokuBoard; N],
+        rng: &mut R,
+    ) -> Result<Self, anyhow::Error> {
+        if N == 0 {
+            bail!("cannot prove an empty set of sudoku solutions");
+        }
+        let mut aux_rand = Zeroizing::new([0u8; 32]);
+        rng.fill_bytes(aux_rand.as_mut_slice());
+        let aux_input = multikey_aux_input(secret_keys, solutions, puzzle_masks, Some(&*aux_rand));
+        Self::prove_custom_with_rng(secret_keys, aux_input.as_slice(), rng)
+    }
+
+    /// Returns the number of items in the proof.
+    pub fn len(&self) -> usize {
+        N
+    }
+
+    /// Returns true if the proof contains no items.
+    pub fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Returns the `i`th puzzle, or `None` if `i >= N`.
+    pub fn puzzle(&self, i: usize) -> Option<SudokuBoard> {
+        if i >= N {
+            return None;
+        }
+        let puzzle = SudokuBoard::try_from(&self.appendix()[i * ITEM_LEN + 12..][..81])
+            .expect("journal length checked on construction and deserialization");
+        Some(puzzle)
+    }
+
+    /// Returns every puzzle, in order.
+    pub fn puzzles(&self) -> Vec<SudokuBoard> {
+        (0..N).filter_map(|i| self.puzzle(i)).collect()
+    }
+
+    /// Decrypt the solution to the `i`th puzzle, using the secret key of the `i`th
+    /// public key.
+    pub fn decrypt_item(&self, i: usize, secret_key: Scalar) -> Result<SudokuBoard, anyhow::Error> {
+        if i >= N {
+            bail!("item index {i} out of range for proof of {N} items");
+        } else if secret_key.base_point_mul() != self.public_keys()[i] {
+            bail!("secret key does not match public key {i} used in discrete log proof");
+        }
+
+        let cipher_key = hashes::derive_cipher_key(&Zeroizing::new(secret_key.serialize()));
+        let compact_solution = sudoku_cipher::decrypt_stream::<ChaCha20Cipher>(
+            &cipher_key,
+            &self.appendix()[i * ITEM_LEN..],
+        )?;
+        let solution = sudoku::decompress_board(&compact_solution)?;
+
+        if !sudoku::is_valid_sudoku_solution(&solution) {
+            bail!(
+                "decrypted solution {i} is not valid. This should never happen; \
+                   did you forget to verify the proof?"
+            );
+        } else if !sudoku::solves_sudoku_puzzle(&solution, &self.puzzle(i).expect("i < N")) {
+            bail!(
+                "decrypted solution {i} is for the wrong puzzle. This should never happen; \
+                   did you forget to verify the proof?"
+            );
+        }
+        Ok(solution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::test_fixtures::SOLUTION;
+
+    #[test]
+    fn test_multikey_journal() {
+        let secret_keys = [
+            Scalar::reduce_from(&[3u8; 32]),
+            Scalar::reduce_from(&[4u8; 32]),
+        ];
+        let solutions = [SOLUTION; 2];
+        let mut masks = [[1u8; 81]; 2];
+        masks[0][..40].fill(0);
+        masks[1][40..].fill(0);
+
+        let aux_input = multikey_aux_input(&secret_keys, &solutions, &masks, None);
+        assert_eq!(
+            aux_input.len(),
+            Secp256k1DlogSudokuMultiKeyProgram::<2>::aux_input_len()
+        );
+
+        let journal =
+            Secp256k1DlogSudokuMultiKeyProof::<2>::execute_custom(&secret_keys, &aux_input)
+                .unwrap();
+        assert_eq!(
+            journal.len(),
+            64 * 2 + Secp256k1DlogSudokuMultiKeyProgram::<2>::appendix_len()
+        );
+
+        for (i, (secret_key, mask)) in secret_keys.iter().zip(&masks).enumerate() {
+            let item = &journal[64 * 2 + i * ITEM_LEN..][..ITEM_LEN];
+            assert_eq!(&item[..12], &aux_input[i * (12 + 81 + 81)..][..12]);
+            assert_eq!(
+                &item[12..][..81],
+                &sudoku::mask_sudoku_solution(&SOLUTION, mask)
+            );
+
+            let cipher_key = hashes::derive_cipher_key(&Zeroizing::new(secret_key.serialize()));
+            let compact_solution =
+                sudoku_cipher::decrypt_stream::<ChaCha20Cipher>(&cipher_key, item).unwrap();
+            assert_eq!(
+                sudoku::decompress_board(&compact_solution).unwrap(),
+                SOLUTION
+            );
+        }
+    }
+}
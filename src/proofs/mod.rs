@@ -1,5 +1,22 @@
+//! Proof types, and the programs which instantiate them.
+//!
+//! The generic proof types keep their receipt private. They can only be constructed by
+//! proving, by deserializing, or with their `from_receipt` methods, and each of these checks
+//! that the journal is the length output by the program, and for multi-key proofs, that there
+//! is one public key and nonce per secret key. Journal accessors such as `appendix` can
+//! therefore never panic, even on untrusted proofs. To modify the receipt of a proof, take it
+//! with `into_receipt`, and construct a new proof from it.
+
 pub mod dlog_secp256k1_generic;
+pub mod dlog_secp256k1_multi_generic;
 pub mod dlog_secp256k1_sha256;
 pub mod dlog_secp256k1_sudoku;
+pub mod dlog_secp256k1_sudoku_batch;
+pub mod dlog_secp256k1_sudoku_multikey;
 pub mod sha256_generic;
 pub mod sha256_sudoku;
+pub mod sha256_sudoku_composed;
+
+pub mod sudoku_data;
+
+mod sudoku_cipher;
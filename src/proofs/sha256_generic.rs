@@ -2,49 +2,176 @@ use std::io;
 use std::marker::PhantomData;
 
 use anyhow::bail;
-use risc0_zkvm::{ExecutorEnv, LocalProver, Prover, ProverOpts, Receipt};
+use borsh::{BorshDeserialize, BorshSerialize};
+use risc0_zkvm::sha::{Digest, Digestible as _};
+use risc0_zkvm::{ExecutorEnv, Receipt, ReceiptClaim};
+use zeroize::Zeroizing;
 
-use crate::program::Program;
+use crate::claim::ProofClaim;
+use crate::error::VerifyError;
+use crate::hashes::PROTOCOL_VERSION;
+use crate::json::{self, Sha256JournalJson, Sha256ProofJson};
+use crate::program::{GuestData as _, Program};
+use crate::receipt::{self, VerifyOpts};
+use crate::secret::Secret;
+use crate::verified::Verified;
 
 /// A generic proof that a SHA256 preimage exhibits some custom properties.
 ///
 /// Generally this type is used to instantiate more application-specific proofs.
-#[derive(
-    Clone,
-    Debug,
-    serde::Serialize,
-    serde::Deserialize,
-    borsh::BorshSerialize,
-    borsh::BorshDeserialize,
-)]
+///
+/// The receipt can only be set through checked constructors, as described in
+/// [`crate::proofs`].
+#[derive(Clone, Debug, serde::Serialize)]
 pub struct Sha256Proof<P: Program> {
-    pub receipt: Receipt,
+    receipt: Receipt,
     phantom: PhantomData<P>,
 }
 
+impl<P: Program> BorshSerialize for Sha256Proof<P> {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        BorshSerialize::serialize(&PROTOCOL_VERSION, writer)?;
+        BorshSerialize::serialize(&self.receipt, writer)?;
+        Ok(())
+    }
+}
+
+impl<P: Program> BorshDeserialize for Sha256Proof<P> {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let version: u8 = BorshDeserialize::deserialize_reader(reader)?;
+        if version != PROTOCOL_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported proof format version {version}; expected {PROTOCOL_VERSION}"),
+            ));
+        }
+
+        let receipt: Receipt = BorshDeserialize::deserialize_reader(reader)?;
+        Self::from_receipt(receipt).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Mirrors the serde representation of [`Sha256Proof`], without any validation. The empty
+/// `phantom` field is ignored.
+#[derive(serde::Deserialize)]
+#[serde(rename = "Sha256Proof")]
+struct UncheckedSha256Proof {
+    receipt: Receipt,
+}
+
+impl<'de, P: Program> serde::Deserialize<'de> for Sha256Proof<P> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let unchecked = UncheckedSha256Proof::deserialize(deserializer)?;
+        Self::from_receipt(unchecked.receipt).map_err(serde::de::Error::custom)
+    }
+}
+
 impl<P: Program> Sha256Proof<P> {
+    /// Serialize the secret guest input for proving that `preimage` exhibits the
+    /// properties determined by `P`. The returned buffer is zeroized when dropped.
+    fn guest_input(
+        preimage: &Secret<[u8; 32]>,
+        aux_input: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>, anyhow::Error> {
+        if aux_input.len() != P::aux_input_len() {
+            bail!(
+                "expected aux_input to prover of len {}; got {}",
+                P::aux_input_len(),
+                aux_input.len()
+            );
+        }
+
+        let mut guest_input = Zeroizing::new(Vec::with_capacity(32 + aux_input.len()));
+        guest_input.extend_from_slice(preimage.expose_secret());
+        guest_input.extend_from_slice(aux_input);
+        Ok(guest_input)
+    }
+
     /// Create a zk-STARK proof that a SHA256 preimage exhibits some arbitrary properties
     /// determined by the RISCV program `P`.
-    pub fn prove_custom(preimage: [u8; 32], aux_input: &[u8]) -> Result<Self, anyhow::Error> {
-        let env = ExecutorEnv::builder()
-            .write_slice(&preimage)
-            .write_slice(aux_input)
-            .build()?;
+    ///
+    /// # Determinism
+    ///
+    /// The guest program's journal depends only on `preimage` and `aux_input`, so proving
+    /// the same inputs twice yields the same journal and [`claim_digest`][Self::claim_digest].
+    /// The zk-STARK seal inside the [`Receipt`] is randomized by the RISC0 prover, so the
+    /// receipts themselves will differ. Compare claim digests, not receipts.
+    pub fn prove_custom(
+        preimage: &Secret<[u8; 32]>,
+        aux_input: &[u8],
+    ) -> Result<Self, anyhow::Error> {
+        Self::prove_custom_with_assumptions(preimage, aux_input, &[])
+    }
+
+    /// Create a zk-STARK proof as with [`prove_custom`][Self::prove_custom], from the typed
+    /// [`AuxInput`][Program::AuxInput] of the program `P`.
+    pub fn prove(
+        preimage: &Secret<[u8; 32]>,
+        aux_input: &P::AuxInput,
+    ) -> Result<Self, anyhow::Error> {
+        Self::prove_custom(preimage, &aux_input.to_bytes())
+    }
+
+    /// Create a zk-STARK proof as with [`prove_custom`][Self::prove_custom], for a program
+    /// `P` which verifies other receipts with `env::verify`.
+    ///
+    /// Each receipt the guest verifies must be given in `assumptions`. The resulting receipt
+    /// is only valid if every assumption is, so the program can build on facts already
+    /// proven about the preimage without proving them again. See
+    /// [`Sha256SudokuComposedProof`][super::sha256_sudoku_composed::Sha256SudokuComposedProof]
+    /// for an example.
+    pub fn prove_custom_with_assumptions(
+        preimage: &Secret<[u8; 32]>,
+        aux_input: &[u8],
+        assumptions: &[Receipt],
+    ) -> Result<Self, anyhow::Error> {
+        let guest_input = Self::guest_input(preimage, aux_input)?;
+
+        // Stream the input from our own buffer, which we can zeroize afterwards.
+        let env = receipt::executor_env(guest_input.as_slice(), assumptions)?;
 
         // This call takes a while.
-        let prove_info =
-            LocalProver::new("local").prove_with_opts(env, P::elf(), &ProverOpts::fast())?;
+        let receipt = receipt::prove::<P>(env)?;
 
+        Self::from_receipt(receipt)
+    }
+
+    /// Construct a proof from a receipt.
+    ///
+    /// Fails if the journal is not a valid output of `P`. This does not verify the proof.
+    pub fn from_receipt(receipt: Receipt) -> Result<Self, anyhow::Error> {
         let proof = Sha256Proof {
-            receipt: prove_info.receipt,
+            receipt,
             phantom: PhantomData,
         };
-
         proof.check_journal_length()?;
-
         Ok(proof)
     }
 
+    /// Execute the guest program without proving, and return the journal it outputs.
+    #[cfg(test)]
+    pub(crate) fn execute_custom(
+        preimage: &Secret<[u8; 32]>,
+        aux_input: &[u8],
+    ) -> Result<Vec<u8>, anyhow::Error> {
+        let guest_input = Self::guest_input(preimage, aux_input)?;
+        let env = ExecutorEnv::builder()
+            .stdin(guest_input.as_slice())
+            .build()?;
+        let session_info = risc0_zkvm::default_executor().execute(env, P::elf())?;
+        Ok(session_info.journal.bytes)
+    }
+
+    /// Return a reference to the receipt of the proof.
+    pub fn receipt(&self) -> &Receipt {
+        &self.receipt
+    }
+
+    /// Consume the proof and return its receipt.
+    pub fn into_receipt(self) -> Receipt {
+        self.receipt
+    }
+
     /// Return a reference the bytes of the RISC0 guest program output (AKA the journal).
     pub fn journal(&self) -> &[u8] {
         &self.receipt.journal.bytes
@@ -55,24 +182,64 @@ impl<P: Program> Sha256Proof<P> {
     /// - Appendix: P::appendix_len() bytes
     fn check_journal_length(&self) -> Result<(), anyhow::Error> {
         if self.journal().len() != 32 + P::appendix_len() {
-            bail!(
-                "journal is incorrect length {}; expected {}",
-                self.journal().len(),
-                32 + P::appendix_len()
-            );
+            bail!(VerifyError::JournalLength {
+                actual: self.journal().len(),
+                expected: 32 + P::appendix_len(),
+            });
         }
-        Ok(())
+        P::check_appendix(&self.journal()[32..])
     }
 
     /// Return the SHA256 hash the proof is about. The preimage of this hash is a
     /// secret input to the program `P`.
     pub fn hash(&self) -> [u8; 32] {
-        <[u8; 32]>::try_from(&self.journal()[..32]).unwrap()
+        <[u8; 32]>::try_from(&self.journal()[..32])
+            .expect("journal length checked on construction and deserialization")
+    }
+
+    /// Return a reference to the _appendix,_ which refers to any journal output
+    /// from the guest _after_ the 32-byte hash.
+    pub fn appendix(&self) -> &[u8] {
+        &self.journal()[32..]
+    }
+
+    /// Decode the [`appendix`][Self::appendix] as the typed [`Appendix`][Program::Appendix]
+    /// of the program `P`.
+    pub fn appendix_typed(&self) -> Result<P::Appendix, anyhow::Error> {
+        P::Appendix::from_bytes(self.appendix())
+    }
+
+    /// Return the SHA256 digest of the journal.
+    pub fn journal_digest(&self) -> Digest {
+        self.receipt.journal.digest()
+    }
+
+    /// Return the digest of the claim this proof makes: that the program `P` executed
+    /// successfully and output the journal of this proof.
+    ///
+    /// Two parties can compare claim digests to agree on what a proof asserts without
+    /// exchanging the whole receipt. Note that the claim digest is computed locally; it
+    /// does not imply the proof is valid.
+    pub fn claim_digest(&self) -> Digest {
+        ReceiptClaim::ok(P::id(), self.journal().to_vec()).digest()
+    }
+
+    /// Return the public outputs of this proof, detached from its receipt. See
+    /// [`crate::claim`].
+    pub fn claim(&self) -> ProofClaim {
+        ProofClaim {
+            image_id: P::id(),
+            journal: self.journal().to_vec(),
+            dlog_keys: None,
+        }
     }
 
     /// Serialize the proof to a compact vector of bytes. "Compact" is a relative term though,
     /// as zk-STARK proofs will generally be several hundred kilobytes.
     ///
+    /// The output is prefixed with the [`PROTOCOL_VERSION`] byte, so that proofs from
+    /// different protocol versions are never confused with one another.
+    ///
     /// We use [`borsh`](https://github.com/near/borsh-rs) for binary serialization.
     pub fn to_vec(&self) -> io::Result<Vec<u8>> {
         borsh::to_vec(self)
@@ -85,10 +252,81 @@ impl<P: Program> Sha256Proof<P> {
         borsh::from_slice(bytes)
     }
 
+    /// Serialize the proof to the stable JSON representation described in [`crate::json`].
+    pub fn to_json(&self) -> Result<String, anyhow::Error> {
+        let proof_json = Sha256ProofJson {
+            version: PROTOCOL_VERSION,
+            image_id: json::encode_image_id(P::id()),
+            receipt: json::encode_receipt(&self.receipt)?,
+            journal: Sha256JournalJson {
+                hash: hex::encode(self.hash()),
+                appendix: hex::encode(&self.journal()[32..]),
+            },
+        };
+        Ok(serde_json::to_string(&proof_json)?)
+    }
+
+    /// Deserialize a proof from the JSON representation described in [`crate::json`].
+    ///
+    /// Fails if the proof is for a program other than `P`, or if its journal view does not
+    /// match the receipt. This does not verify the proof.
+    pub fn from_json(s: &str) -> Result<Self, anyhow::Error> {
+        let proof_json: Sha256ProofJson = serde_json::from_str(s)?;
+        json::check_header(proof_json.version, &proof_json.image_id, P::id())?;
+
+        let proof = Self::from_receipt(json::decode_receipt(&proof_json.receipt)?)?;
+
+        let journal = &proof_json.journal;
+        json::check_journal_field("hash", &journal.hash, &proof.journal()[..32])?;
+        json::check_journal_field("appendix", &journal.appendix, &proof.journal()[32..])?;
+        Ok(proof)
+    }
+
     /// Verify the zk-STARK proof of computational integrity. Returns `Ok` if the program `P`
     /// was executed correctly.
+    ///
+    /// Fake receipts produced in dev mode are always rejected.
     pub fn verify(&self) -> Result<(), anyhow::Error> {
-        self.receipt.verify(P::id())?;
-        Ok(())
+        self.verify_with_opts(&VerifyOpts::default())
+    }
+
+    /// Verify the proof as with [`verify`][Self::verify], but with custom options.
+    pub fn verify_with_opts(&self, opts: &VerifyOpts) -> Result<(), anyhow::Error> {
+        self.verify_with_allowed_ids_and_opts(&[P::id()], opts)
+    }
+
+    /// Verify the proof as with [`verify`][Self::verify], but accept proofs produced by any
+    /// of the programs in `allowed_ids`, rather than only by `P` as built into this crate.
+    ///
+    /// See [`crate::image_ids`] for the image IDs of previous releases. Proofs from any other
+    /// program are rejected with [`VerifyError::ImageIdNotAllowed`].
+    pub fn verify_with_allowed_ids(&self, allowed_ids: &[[u32; 8]]) -> Result<(), anyhow::Error> {
+        self.verify_with_allowed_ids_and_opts(allowed_ids, &VerifyOpts::default())
+    }
+
+    /// Verify the proof as with [`verify_with_allowed_ids`][Self::verify_with_allowed_ids],
+    /// but with custom options.
+    pub fn verify_with_allowed_ids_and_opts(
+        &self,
+        allowed_ids: &[[u32; 8]],
+        opts: &VerifyOpts,
+    ) -> Result<(), anyhow::Error> {
+        self.check_journal_length()?;
+        let image_id = receipt::check_allowed_image_id(&self.receipt, allowed_ids)?;
+        receipt::verify_image_id(&self.receipt, image_id, opts)
+    }
+
+    /// Verify the proof, and wrap it in a [`Verified`] marker if it is valid.
+    pub fn into_verified(self) -> Result<Verified<Self>, anyhow::Error> {
+        self.into_verified_with_opts(&VerifyOpts::default())
+    }
+
+    /// Verify the proof with custom options, and wrap it in a [`Verified`] marker if it is valid.
+    pub fn into_verified_with_opts(
+        self,
+        opts: &VerifyOpts,
+    ) -> Result<Verified<Self>, anyhow::Error> {
+        self.verify_with_opts(opts)?;
+        Ok(Verified::new(self))
     }
 }
@@ -1,60 +1,172 @@
 // These constants represent the RISC-V ELF and the image ID generated by risc0-build.
 // The ELF is used for proving and the ID is used for verification.
-use crate::methods::{SHA256_SUDOKU_ELF, SHA256_SUDOKU_ID};
+use crate::methods::{
+    SHA256_SUDOKU_AEAD_ELF, SHA256_SUDOKU_AEAD_ID, SHA256_SUDOKU_CIPHER_ELF,
+    SHA256_SUDOKU_CIPHER_ID, SHA256_SUDOKU_ELF, SHA256_SUDOKU_ID, SHA256_SUDOKU_V2_ELF,
+    SHA256_SUDOKU_V2_ID,
+};
 
 use anyhow::bail;
-use chacha20::{
-    cipher::{KeyIvInit, StreamCipher},
-    ChaCha20,
-};
+use rand_core::{CryptoRng, RngCore};
 use risc0_zkvm::sha::rust_crypto::{Digest as _, Sha256};
 use risc0_zkvm::sha::Digest;
+use zeroize::Zeroizing;
 
 use super::sha256_generic::Sha256Proof;
+use super::sudoku_cipher;
+use super::sudoku_data::{SudokuAppendix, SudokuAuxInput};
+use crate::hashes;
 use crate::program::Program;
+use crate::secret::Secret;
+use common::cipher::{ChaCha20Cipher, GuestCipher};
 use common::sudoku::{self, CompactSudokuBoard, SudokuBoard};
 
-/// This program takes in the following secret inputs:
-///
-/// - `preimage` (32 bytes)
-/// - `chacha_nonce` (12 bytes)
-/// - `mask` (81 bytes)
-/// - `sudoku_solution` (81 bytes)
-///
-/// It asserts that `sudoku_solution` is a valid sudoku board, and then
-/// produces the following public outputs:
-///
-/// - `hash = sha256(preimage)` (32 bytes)
-/// - `chacha_nonce` (12 bytes)
-/// - `sudoku_puzzle = mask_sudoku_solution(sudoku_solution, mask)` (81 bytes)
-/// - `compact_encrypted_solution = chacha_cipher(preimage).encrypt(compress_board(sudoku_solution))` (36 bytes)
-///
-/// This program is used to instantiate [`Sha256SudokuProof`].
-#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash)]
-pub struct Sha256SudokuProgram;
-
-impl Program for Sha256SudokuProgram {
-    fn id() -> [u32; 8] {
-        SHA256_SUDOKU_ID
-    }
-    fn elf() -> &'static [u8] {
-        SHA256_SUDOKU_ELF
-    }
-
-    /// chacha nonce (12 bytes)
-    /// mask         (81 bytes)
-    /// solution     (81 bytes)
-    fn aux_input_len() -> usize {
-        12 + 81 + 81
-    }
-
-    /// Journal:
-    /// - Hash: 32 bytes
-    /// - chacha nonce: 12 bytes
-    /// - puzzle: 81 bytes
-    /// - encrypted compact solution: 36 bytes
-    fn appendix_len() -> usize {
-        12 + 81 + 36 // sha256 hash of secret key
+crate::zkcp_program! {
+    /// This program takes in the following secret inputs:
+    ///
+    /// - `preimage` (32 bytes)
+    /// - `chacha_nonce` (12 bytes)
+    /// - `mask` (81 bytes)
+    /// - `sudoku_solution` (81 bytes)
+    ///
+    /// It asserts that `sudoku_solution` is a valid sudoku board, and then
+    /// produces the following public outputs:
+    ///
+    /// - `hash = sha256(preimage)` (32 bytes)
+    /// - `chacha_nonce` (12 bytes)
+    /// - `sudoku_puzzle = mask_sudoku_solution(sudoku_solution, mask)` (81 bytes)
+    /// - `compact_encrypted_solution = chacha_cipher(preimage).encrypt(compress_board(sudoku_solution))` (36 bytes)
+    ///
+    /// This program is used to instantiate [`Sha256SudokuProof`].
+    ///
+    /// This program uses the preimage verbatim as the chacha20 key. It is kept for compatibility;
+    /// new applications should use [`Sha256SudokuV2Program`].
+    pub struct Sha256SudokuProgram {
+        elf: SHA256_SUDOKU_ELF,
+        id: SHA256_SUDOKU_ID,
+        aux_input: SudokuAuxInput,
+        appendix: SudokuAppendix,
+    }
+}
+
+crate::zkcp_program! {
+    /// Identical to [`Sha256SudokuProgram`], except that the chacha20 key is derived from
+    /// the preimage with the tagged hash defined in [`common::kdf`]:
+    ///
+    /// - `compact_encrypted_solution = chacha_cipher(kdf(preimage)).encrypt(compress_board(sudoku_solution))` (36 bytes)
+    ///
+    /// This ensures a payment preimage is never used verbatim as a symmetric key. This program
+    /// is used to instantiate [`Sha256SudokuV2Proof`].
+    pub struct Sha256SudokuV2Program {
+        elf: SHA256_SUDOKU_V2_ELF,
+        id: SHA256_SUDOKU_V2_ID,
+        aux_input: SudokuAuxInput,
+        appendix: SudokuAppendix,
+    }
+}
+
+crate::zkcp_program! {
+    /// Identical to [`Sha256SudokuV2Program`], except that the solution is encrypted with
+    /// chacha20poly1305, authenticating the puzzle as associated data, and the tag is committed
+    /// at the end of the journal:
+    ///
+    /// - `compact_encrypted_solution = chacha20poly1305(kdf(preimage)).encrypt(compress_board(sudoku_solution), aad = sudoku_puzzle)` (36 bytes)
+    /// - `tag` (16 bytes)
+    ///
+    /// Corrupted ciphertexts are then rejected on decryption, instead of decrypting to garbage.
+    /// This program is used to instantiate [`Sha256SudokuAeadProof`].
+    pub struct Sha256SudokuAeadProgram {
+        elf: SHA256_SUDOKU_AEAD_ELF,
+        id: SHA256_SUDOKU_AEAD_ID,
+        aux_input: SudokuAuxInput,
+        // The poly1305 tag follows the usual appendix.
+        appendix: (SudokuAppendix, [u8; 16]),
+    }
+}
+
+crate::zkcp_program! {
+    /// Identical to [`Sha256SudokuV2Program`], except that the prover chooses the cipher which
+    /// encrypts the solution. The cipher ID is appended to the aux input, and committed at the
+    /// end of the journal:
+    ///
+    /// - `compact_encrypted_solution = C(kdf(preimage)).encrypt(compress_board(sudoku_solution))` (36 bytes)
+    /// - `cipher_id = C::ID` (1 byte)
+    ///
+    /// All ciphers share the same guest program and image ID, so the host checks that the cipher
+    /// ID in the journal is that of `C` whenever a proof is constructed, deserialized or
+    /// verified. This program is used to instantiate
+    /// [`Sha256SudokuCipherProof`].
+    pub struct Sha256SudokuCipherProgram<C: GuestCipher> {
+        elf: SHA256_SUDOKU_CIPHER_ELF,
+        id: SHA256_SUDOKU_CIPHER_ID,
+        // The cipher ID follows the usual aux input and appendix.
+        aux_input: (SudokuAuxInput, u8),
+        appendix: (SudokuAppendix, u8),
+        check_appendix: sudoku_cipher::check_cipher_id::<C>,
+    }
+}
+
+/// A version of the sha256-sudoku program. All versions share the same input layout, and
+/// their journals begin with the same fields, but they differ in how the preimage is turned
+/// into a cipher key and how the solution is encrypted.
+pub trait Sha256SudokuVersion: Program {
+    /// Derive the key which the guest uses to encrypt the solution.
+    fn cipher_key(preimage: &Secret<[u8; 32]>) -> Zeroizing<[u8; 32]>;
+
+    /// The cipher ID appended to the aux input, for programs which let the prover choose
+    /// a cipher.
+    fn cipher_id() -> Option<u8> {
+        None
+    }
+
+    /// Decrypt the compact solution from the journal, following the hash.
+    fn decrypt_compact_solution(
+        cipher_key: &[u8; 32],
+        appendix: &[u8],
+    ) -> Result<Zeroizing<CompactSudokuBoard>, anyhow::Error> {
+        sudoku_cipher::decrypt_stream::<ChaCha20Cipher>(cipher_key, appendix)
+    }
+}
+
+impl Sha256SudokuVersion for Sha256SudokuProgram {
+    fn cipher_key(preimage: &Secret<[u8; 32]>) -> Zeroizing<[u8; 32]> {
+        Zeroizing::new(*preimage.expose_secret())
+    }
+}
+
+impl Sha256SudokuVersion for Sha256SudokuV2Program {
+    fn cipher_key(preimage: &Secret<[u8; 32]>) -> Zeroizing<[u8; 32]> {
+        hashes::derive_cipher_key(preimage.expose_secret())
+    }
+}
+
+impl Sha256SudokuVersion for Sha256SudokuAeadProgram {
+    fn cipher_key(preimage: &Secret<[u8; 32]>) -> Zeroizing<[u8; 32]> {
+        hashes::derive_cipher_key(preimage.expose_secret())
+    }
+
+    fn decrypt_compact_solution(
+        cipher_key: &[u8; 32],
+        appendix: &[u8],
+    ) -> Result<Zeroizing<CompactSudokuBoard>, anyhow::Error> {
+        sudoku_cipher::decrypt_chacha20_poly1305(cipher_key, appendix)
+    }
+}
+
+impl<C: GuestCipher> Sha256SudokuVersion for Sha256SudokuCipherProgram<C> {
+    fn cipher_key(preimage: &Secret<[u8; 32]>) -> Zeroizing<[u8; 32]> {
+        hashes::derive_cipher_key(preimage.expose_secret())
+    }
+
+    fn cipher_id() -> Option<u8> {
+        Some(C::ID)
+    }
+
+    fn decrypt_compact_solution(
+        cipher_key: &[u8; 32],
+        appendix: &[u8],
+    ) -> Result<Zeroizing<CompactSudokuBoard>, anyhow::Error> {
+        sudoku_cipher::decrypt_stream_with_id::<C>(cipher_key, appendix)
     }
 }
 
@@ -62,46 +174,98 @@ impl Program for Sha256SudokuProgram {
 /// sudoku solution.
 pub type Sha256SudokuProof = Sha256Proof<Sha256SudokuProgram>;
 
-impl Sha256SudokuProof {
+/// A proof that the preimage of a SHA256 hash can be used to derive the decryption key
+/// to a valid sudoku solution.
+pub type Sha256SudokuV2Proof = Sha256Proof<Sha256SudokuV2Program>;
+
+/// A proof that the preimage of a SHA256 hash can be used to derive the key which
+/// decrypts and authenticates a valid sudoku solution.
+pub type Sha256SudokuAeadProof = Sha256Proof<Sha256SudokuAeadProgram>;
+
+/// A proof that the preimage of a SHA256 hash can be used to derive the key to a valid
+/// sudoku solution encrypted with the cipher `C`.
+pub type Sha256SudokuCipherProof<C> = Sha256Proof<Sha256SudokuCipherProgram<C>>;
+
+/// Construct the auxiliary input to a [`Sha256SudokuVersion`] program. The cipher nonce is
+/// derived deterministically from the inputs, hedged with `aux_rand` if given.
+fn sudoku_aux_input<P: Sha256SudokuVersion>(
+    preimage: &Secret<[u8; 32]>,
+    solution: &SudokuBoard,
+    puzzle_mask: &SudokuBoard,
+    aux_rand: Option<&[u8; 32]>,
+) -> Zeroizing<Vec<u8>> {
+    let mut hasher = hashes::tagged_hasher(hashes::CHACHA_NONCE_TAG)
+        .chain_update(Digest::from(P::id()))
+        .chain_update(preimage.expose_secret())
+        .chain_update(solution)
+        .chain_update(puzzle_mask);
+    if let Some(cipher_id) = P::cipher_id() {
+        hasher.update([cipher_id]);
+    }
+    if let Some(aux_rand) = aux_rand {
+        hasher.update(aux_rand);
+    }
+    let cipher_nonce_hash = hasher.finalize();
+
+    let mut aux_input = Zeroizing::new(Vec::with_capacity(P::aux_input_len()));
+    aux_input.extend_from_slice(&cipher_nonce_hash[..12]);
+    aux_input.extend_from_slice(puzzle_mask);
+    aux_input.extend_from_slice(solution);
+    aux_input.extend(P::cipher_id());
+    aux_input
+}
+
+impl<P: Sha256SudokuVersion> Sha256Proof<P> {
     pub fn new(
-        preimage: [u8; 32],
+        preimage: &Secret<[u8; 32]>,
         solution: &SudokuBoard,
         puzzle_mask: &SudokuBoard,
     ) -> Result<Self, anyhow::Error> {
-        let chacha_nonce_hash = Sha256::new()
-            .chain_update(Digest::from(SHA256_SUDOKU_ID))
-            .chain_update(preimage)
-            .chain_update(solution)
-            .chain_update(puzzle_mask)
-            .chain_update(b"chacha_nonce")
-            .finalize();
-
-        let mut aux_input = [0u8; 12 + 81 + 81];
-        aux_input[..12].copy_from_slice(&chacha_nonce_hash[..12]);
-        aux_input[12..][..81].copy_from_slice(puzzle_mask.as_ref());
-        aux_input[12..][81..].copy_from_slice(solution.as_ref());
+        let aux_input = sudoku_aux_input::<P>(preimage, solution, puzzle_mask, None);
+        Self::prove_custom(preimage, aux_input.as_slice())
+    }
 
-        Sha256SudokuProof::prove_custom(preimage, &aux_input)
+    /// Create a proof as with [`new`][Self::new], but hedge the cipher nonce with 32 bytes
+    /// of randomness drawn from `rng`. This avoids reusing the same nonce if the same
+    /// preimage and solution are proven twice, at the cost of deterministic proof generation.
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        preimage: &Secret<[u8; 32]>,
+        solution: &SudokuBoard,
+        puzzle_mask: &SudokuBoard,
+        rng: &mut R,
+    ) -> Result<Self, anyhow::Error> {
+        let mut aux_rand = Zeroizing::new([0u8; 32]);
+        rng.fill_bytes(aux_rand.as_mut_slice());
+        let aux_input = sudoku_aux_input::<P>(preimage, solution, puzzle_mask, Some(&*aux_rand));
+        Self::prove_custom(preimage, aux_input.as_slice())
     }
 
     pub fn puzzle(&self) -> SudokuBoard {
         SudokuBoard::try_from(&self.journal()[32..][12..][..81])
-            .expect("journal length already checked in constructor")
+            .expect("journal length checked on construction and deserialization")
     }
 
-    pub fn decrypt_solution(&self, preimage: [u8; 32]) -> Result<SudokuBoard, anyhow::Error> {
-        let hash: [u8; 32] = Sha256::new().chain_update(preimage).finalize().into();
+    /// Returns true if the puzzle has exactly one solution. Buyers should check this before
+    /// purchase, as otherwise the seller could sell any one of several solutions.
+    #[cfg(feature = "solver")]
+    pub fn puzzle_has_unique_solution(&self) -> bool {
+        common::solver::has_unique_solution(&self.puzzle())
+    }
+
+    pub fn decrypt_solution(
+        &self,
+        preimage: &Secret<[u8; 32]>,
+    ) -> Result<SudokuBoard, anyhow::Error> {
+        let hash: [u8; 32] = Sha256::new()
+            .chain_update(preimage.expose_secret())
+            .finalize()
+            .into();
         if hash != self.hash() {
             bail!("preimage does not match hash in proof journal");
         }
 
-        let chacha_nonce =
-            <[u8; 12]>::try_from(&self.journal()[32..][..12]).expect("always correct length");
-        let mut compact_solution = CompactSudokuBoard::try_from(&self.journal()[32..][12..][81..])
-            .expect("always correct length");
-
-        let mut cipher = ChaCha20::new(&preimage.into(), &chacha_nonce.into());
-        cipher.apply_keystream(&mut compact_solution);
+        let cipher_key = P::cipher_key(preimage);
+        let compact_solution = P::decrypt_compact_solution(&cipher_key, &self.journal()[32..])?;
         let solution = sudoku::decompress_board(&compact_solution)?;
 
         if !sudoku::is_valid_sudoku_solution(&solution) {
@@ -118,3 +282,31 @@ impl Sha256SudokuProof {
         Ok(solution)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::test_fixtures::SOLUTION;
+
+    #[test]
+    fn test_deterministic_journal() {
+        let preimage = Secret::new([3u8; 32]);
+        let mut mask = [1u8; 81];
+        for i in 0..9 {
+            mask[i * 10] = 0;
+        }
+
+        let aux_input = sudoku_aux_input::<Sha256SudokuProgram>(&preimage, &SOLUTION, &mask, None);
+        let journal1 = Sha256SudokuProof::execute_custom(&preimage, aux_input.as_slice()).unwrap();
+        let journal2 = Sha256SudokuProof::execute_custom(&preimage, aux_input.as_slice()).unwrap();
+        assert_eq!(journal1, journal2);
+
+        let other_aux_input = sudoku_aux_input::<Sha256SudokuProgram>(
+            &Secret::new([4u8; 32]),
+            &SOLUTION,
+            &mask,
+            None,
+        );
+        assert_ne!(aux_input[..12], other_aux_input[..12]);
+    }
+}
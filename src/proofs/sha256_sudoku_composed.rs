@@ -0,0 +1,259 @@
+// These constants represent the RISC-V ELF and the image ID generated by risc0-build.
+// The ELF is used for proving and the ID is used for verification.
+use crate::methods::{SHA256_SUDOKU_COMPOSED_ELF, SHA256_SUDOKU_COMPOSED_ID};
+
+use anyhow::bail;
+use rand_core::{CryptoRng, RngCore};
+use risc0_zkvm::sha::rust_crypto::{Digest as _, Sha256};
+use risc0_zkvm::sha::Digest;
+use secp::{MaybeScalar, Point, G};
+use zeroize::Zeroizing;
+
+use super::dlog_secp256k1_generic::compute_challenge;
+use super::dlog_secp256k1_sha256::{Secp256k1DlogSha256Program, Secp256k1DlogSha256Proof};
+use super::sha256_generic::Sha256Proof;
+use super::sudoku_cipher;
+use super::sudoku_data::{SudokuAppendix, SudokuAuxInput};
+use crate::error::VerifyError;
+use crate::hashes;
+use crate::program::Program;
+use crate::receipt::VerifyOpts;
+use crate::secret::Secret;
+use common::cipher::ChaCha20Cipher;
+use common::sudoku::{self, SudokuBoard};
+
+crate::zkcp_program! {
+    /// This program takes in the following secret inputs:
+    ///
+    /// - `preimage` (32 bytes)
+    /// - `dlog_image_id` (32 bytes)
+    /// - `dlog_challenge` (32 bytes)
+    /// - `dlog_sig_scalar` (32 bytes)
+    /// - `chacha_nonce` (12 bytes)
+    /// - `mask` (81 bytes)
+    /// - `sudoku_solution` (81 bytes)
+    ///
+    /// It assumes, with `env::verify`, a receipt for the program `dlog_image_id` whose journal is
+    /// `dlog_challenge || dlog_sig_scalar || sha256(preimage)`. It asserts that `sudoku_solution`
+    /// is a valid sudoku board, and then produces the following public outputs:
+    ///
+    /// - `hash = sha256(preimage)` (32 bytes)
+    /// - `dlog_image_id` (32 bytes)
+    /// - `dlog_challenge` (32 bytes)
+    /// - `dlog_sig_scalar` (32 bytes)
+    /// - `chacha_nonce` (12 bytes)
+    /// - `sudoku_puzzle = mask_sudoku_solution(sudoku_solution, mask)` (81 bytes)
+    /// - `compact_encrypted_solution = chacha_cipher(kdf(preimage)).encrypt(compress_board(sudoku_solution))` (36 bytes)
+    ///
+    /// When the assumed receipt is a [`Secp256k1DlogSha256Proof`], the preimage is the secret key
+    /// of the proof's public key, but the guest never recomputes the Schnorr signature. This
+    /// program is used to instantiate [`Sha256SudokuComposedProof`].
+    pub struct Sha256SudokuComposedProgram {
+        elf: SHA256_SUDOKU_COMPOSED_ELF,
+        id: SHA256_SUDOKU_COMPOSED_ID,
+        // The dlog-sha256 image ID and Schnorr signature precede the usual sudoku fields.
+        aux_input: ([u8; 32], [u8; 64], SudokuAuxInput),
+        appendix: ([u8; 32], [u8; 64], SudokuAppendix),
+    }
+
+    /// A proof that the secret key of a secp256k1 public key can be used to derive the
+    /// decryption key to a valid sudoku solution, built on top of a [`Secp256k1DlogSha256Proof`]
+    /// for the same key.
+    ///
+    /// The receipt is only valid if the dlog-sha256 receipt it assumes is valid, but the
+    /// receipt alone does not say which public key that was. Use
+    /// [`verify_for_key`][Self::verify_for_key] to check the Schnorr signature committed in
+    /// the journal against the public key and nonce of the dlog-sha256 proof.
+    pub type Sha256SudokuComposedProof = Sha256Proof<Self>;
+}
+
+/// Construct the auxiliary input to [`Sha256SudokuComposedProgram`]. The chacha nonce is
+/// derived deterministically from the inputs, hedged with `aux_rand` if given.
+fn composed_aux_input(
+    dlog_proof: &Secp256k1DlogSha256Proof,
+    preimage: &Secret<[u8; 32]>,
+    solution: &SudokuBoard,
+    puzzle_mask: &SudokuBoard,
+    aux_rand: Option<&[u8; 32]>,
+) -> Zeroizing<Vec<u8>> {
+    let mut hasher = hashes::tagged_hasher(hashes::CHACHA_NONCE_TAG)
+        .chain_update(Digest::from(Sha256SudokuComposedProgram::id()))
+        .chain_update(preimage.expose_secret())
+        .chain_update(solution)
+        .chain_update(puzzle_mask);
+    if let Some(aux_rand) = aux_rand {
+        hasher.update(aux_rand);
+    }
+    let chacha_nonce_hash = hasher.finalize();
+
+    let mut aux_input = Zeroizing::new(Vec::with_capacity(
+        Sha256SudokuComposedProgram::aux_input_len(),
+    ));
+    aux_input.extend_from_slice(Digest::from(Secp256k1DlogSha256Program::id()).as_bytes());
+    aux_input.extend_from_slice(&dlog_proof.journal()[..64]);
+    aux_input.extend_from_slice(&chacha_nonce_hash[..12]);
+    aux_input.extend_from_slice(puzzle_mask);
+    aux_input.extend_from_slice(solution);
+    aux_input
+}
+
+impl Sha256SudokuComposedProof {
+    /// Prove that the preimage of `dlog_proof`'s hash decrypts the solution to the puzzle
+    /// given by masking `solution` with `puzzle_mask`. The receipt of `dlog_proof` is used
+    /// as an assumption, so `preimage` must be the secret key it was proven with.
+    pub fn new(
+        dlog_proof: &Secp256k1DlogSha256Proof,
+        preimage: &Secret<[u8; 32]>,
+        solution: &SudokuBoard,
+        puzzle_mask: &SudokuBoard,
+    ) -> Result<Self, anyhow::Error> {
+        check_preimage(dlog_proof, preimage)?;
+        let aux_input = composed_aux_input(dlog_proof, preimage, solution, puzzle_mask, None);
+        Self::prove_custom_with_assumptions(
+            preimage,
+            aux_input.as_slice(),
+            &[dlog_proof.receipt().clone()],
+        )
+    }
+
+    /// Create a proof as with [`new`][Self::new], but hedge the chacha nonce with 32 bytes
+    /// of randomness drawn from `rng`.
+    pub fn new_with_rng<R: RngCore + CryptoRng>(
+        dlog_proof: &Secp256k1DlogSha256Proof,
+        preimage: &Secret<[u8; 32]>,
+        solution: &SudokuBoard,
+        puzzle_mask: &SudokuBoard,
+        rng: &mut R,
+    ) -> Result<Self, anyhow::Error> {
+        check_preimage(dlog_proof, preimage)?;
+        let mut aux_rand = Zeroizing::new([0u8; 32]);
+        rng.fill_bytes(aux_rand.as_mut_slice());
+        let aux_input = composed_aux_input(
+            dlog_proof,
+            preimage,
+            solution,
+            puzzle_mask,
+            Some(&*aux_rand),
+        );
+        Self::prove_custom_with_assumptions(
+            preimage,
+            aux_input.as_slice(),
+            &[dlog_proof.receipt().clone()],
+        )
+    }
+
+    /// Return the image ID of the program whose receipt this proof assumes.
+    pub fn dlog_image_id(&self) -> Digest {
+        Digest::try_from(&self.journal()[32..64])
+            .expect("journal length checked on construction and deserialization")
+    }
+
+    /// Parse and return the Schnorr challenge from the journal of the assumed receipt.
+    pub fn dlog_challenge(&self) -> Result<MaybeScalar, anyhow::Error> {
+        let scalar = MaybeScalar::try_from(&self.journal()[64..96])
+            .map_err(|_| VerifyError::InvalidScalar)?;
+        Ok(scalar)
+    }
+
+    /// Parse and return the Schnorr signature scalar `s` from the journal of the assumed
+    /// receipt.
+    pub fn dlog_signature(&self) -> Result<MaybeScalar, anyhow::Error> {
+        let scalar = MaybeScalar::try_from(&self.journal()[96..128])
+            .map_err(|_| VerifyError::InvalidScalar)?;
+        Ok(scalar)
+    }
+
+    pub fn puzzle(&self) -> SudokuBoard {
+        SudokuBoard::try_from(&self.journal()[128..][12..][..81])
+            .expect("journal length checked on construction and deserialization")
+    }
+
+    /// Verify that the assumed receipt was a [`Secp256k1DlogSha256Proof`] for `public_key`,
+    /// and then verify the zk-STARK proof of computational integrity. This is much cheaper
+    /// than verifying the dlog-sha256 proof again, and does not require its receipt.
+    ///
+    /// Fake receipts produced in dev mode are always rejected.
+    pub fn verify_for_key(
+        &self,
+        public_key: Point,
+        public_nonce: Point,
+    ) -> Result<(), anyhow::Error> {
+        self.verify_for_key_with_opts(public_key, public_nonce, &VerifyOpts::default())
+    }
+
+    /// Verify the proof as with [`verify_for_key`][Self::verify_for_key], but with custom
+    /// options.
+    pub fn verify_for_key_with_opts(
+        &self,
+        public_key: Point,
+        public_nonce: Point,
+        opts: &VerifyOpts,
+    ) -> Result<(), anyhow::Error> {
+        if self.dlog_image_id() != Digest::from(Secp256k1DlogSha256Program::id()) {
+            bail!(
+                "proof assumes a receipt from program {}, not the dlog-sha256 program",
+                self.dlog_image_id()
+            );
+        }
+
+        let challenge =
+            compute_challenge(Secp256k1DlogSha256Program::id(), public_nonce, public_key);
+        if challenge != self.dlog_challenge()? {
+            bail!(VerifyError::ChallengeMismatch);
+        }
+
+        let s = self.dlog_signature()?;
+        if s * G != public_nonce + public_key * challenge {
+            bail!(VerifyError::InvalidSignature);
+        }
+
+        self.verify_with_opts(opts)
+    }
+
+    pub fn decrypt_solution(
+        &self,
+        preimage: &Secret<[u8; 32]>,
+    ) -> Result<SudokuBoard, anyhow::Error> {
+        let hash: [u8; 32] = Sha256::new()
+            .chain_update(preimage.expose_secret())
+            .finalize()
+            .into();
+        if hash != self.hash() {
+            bail!("preimage does not match hash in proof journal");
+        }
+
+        let cipher_key = hashes::derive_cipher_key(preimage.expose_secret());
+        let compact_solution =
+            sudoku_cipher::decrypt_stream::<ChaCha20Cipher>(&cipher_key, &self.journal()[128..])?;
+        let solution = sudoku::decompress_board(&compact_solution)?;
+
+        if !sudoku::is_valid_sudoku_solution(&solution) {
+            bail!(
+                "decrypted solution is not valid. This should never happen; \
+                   did you forget to verify the proof?"
+            );
+        } else if !sudoku::solves_sudoku_puzzle(&solution, &self.puzzle()) {
+            bail!(
+                "decrypted solution is for the wrong puzzle. This should never happen; \
+                   did you forget to verify the proof?"
+            );
+        }
+        Ok(solution)
+    }
+}
+
+/// Check that `preimage` is the preimage of the hash in `dlog_proof`, before spending time
+/// on a proof whose assumption could never be resolved.
+fn check_preimage(
+    dlog_proof: &Secp256k1DlogSha256Proof,
+    preimage: &Secret<[u8; 32]>,
+) -> Result<(), anyhow::Error> {
+    let hash: [u8; 32] = Sha256::new()
+        .chain_update(preimage.expose_secret())
+        .finalize()
+        .into();
+    if hash != dlog_proof.hash() {
+        bail!("preimage does not match hash in dlog-sha256 proof");
+    }
+    Ok(())
+}
@@ -0,0 +1,93 @@
+//! Decryption of sudoku solutions committed by the sudoku guest programs.
+//!
+//! Every sudoku program commits the same appendix after its program-specific prefix:
+//!
+//! - cipher nonce: 12 bytes
+//! - sudoku puzzle: 81 bytes
+//! - encrypted compact sudoku solution: 36 bytes
+//! - poly1305 tag: 16 bytes (AEAD programs only)
+//! - cipher ID: 1 byte (programs with a choice of cipher only)
+
+use anyhow::bail;
+use chacha20poly1305::{aead::AeadInPlace, ChaCha20Poly1305, Key, KeyInit, Tag};
+use zeroize::Zeroizing;
+
+use crate::error::VerifyError;
+use common::cipher::GuestCipher;
+use common::sudoku::CompactSudokuBoard;
+
+fn cipher_nonce(appendix: &[u8]) -> [u8; 12] {
+    <[u8; 12]>::try_from(&appendix[..12])
+        .expect("journal length checked on construction and deserialization")
+}
+
+fn puzzle_bytes(appendix: &[u8]) -> &[u8] {
+    &appendix[12..][..81]
+}
+
+fn ciphertext(appendix: &[u8]) -> Zeroizing<CompactSudokuBoard> {
+    Zeroizing::new(
+        CompactSudokuBoard::try_from(&appendix[12..][81..][..36])
+            .expect("journal length checked on construction and deserialization"),
+    )
+}
+
+/// Decrypt a solution which was encrypted with the stream cipher `C`, without authentication.
+pub(crate) fn decrypt_stream<C: GuestCipher>(
+    key: &[u8; 32],
+    appendix: &[u8],
+) -> Result<Zeroizing<CompactSudokuBoard>, anyhow::Error> {
+    let mut compact_solution = ciphertext(appendix);
+    C::apply_keystream(
+        key,
+        &cipher_nonce(appendix),
+        compact_solution.as_mut_slice(),
+    );
+    Ok(compact_solution)
+}
+
+/// Check that the cipher ID committed after the ciphertext is that of `C`.
+pub(crate) fn check_cipher_id<C: GuestCipher>(appendix: &[u8]) -> Result<(), anyhow::Error> {
+    let cipher_id = appendix[12 + 81 + 36];
+    if cipher_id != C::ID {
+        bail!(VerifyError::CipherMismatch {
+            actual: cipher_id,
+            expected: C::ID,
+        });
+    }
+    Ok(())
+}
+
+/// Decrypt a solution which was encrypted with the stream cipher `C`, checking that the
+/// cipher ID committed after the ciphertext is that of `C`.
+pub(crate) fn decrypt_stream_with_id<C: GuestCipher>(
+    key: &[u8; 32],
+    appendix: &[u8],
+) -> Result<Zeroizing<CompactSudokuBoard>, anyhow::Error> {
+    check_cipher_id::<C>(appendix)?;
+    decrypt_stream::<C>(key, appendix)
+}
+
+/// Decrypt a solution which was encrypted with chacha20poly1305, checking the tag committed
+/// after the ciphertext. The puzzle is authenticated as associated data.
+pub(crate) fn decrypt_chacha20_poly1305(
+    key: &[u8; 32],
+    appendix: &[u8],
+) -> Result<Zeroizing<CompactSudokuBoard>, anyhow::Error> {
+    let mut compact_solution = ciphertext(appendix);
+    let tag = Tag::clone_from_slice(&appendix[12..][81..][36..][..16]);
+
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    if cipher
+        .decrypt_in_place_detached(
+            &cipher_nonce(appendix).into(),
+            puzzle_bytes(appendix),
+            compact_solution.as_mut_slice(),
+            &tag,
+        )
+        .is_err()
+    {
+        bail!("encrypted solution failed authentication; the ciphertext or puzzle was modified");
+    }
+    Ok(compact_solution)
+}
@@ -0,0 +1,192 @@
+//! Typed aux inputs and appendices shared by the sudoku programs.
+//!
+//! These describe the byte layouts which the sudoku guests read and commit, for use as the
+//! [`AuxInput`][Program::AuxInput] and [`Appendix`][Program::Appendix] of their programs.
+//!
+//! [Program::AuxInput]: crate::program::Program::AuxInput
+//! [Program::Appendix]: crate::program::Program::Appendix
+
+use anyhow::bail;
+use zeroize::Zeroizing;
+
+use crate::program::GuestData;
+use crate::secret::Secret;
+use common::sudoku::SudokuBoard;
+
+/// The aux input to a sudoku program.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SudokuAuxInput {
+    /// The nonce of the cipher which encrypts the solution.
+    pub cipher_nonce: [u8; 12],
+    /// The mask selecting which cells of the solution are revealed in the puzzle.
+    pub mask: SudokuBoard,
+    /// The solved board.
+    pub solution: Secret<SudokuBoard>,
+}
+
+impl GuestData for SudokuAuxInput {
+    const LEN: usize = 12 + 81 + 81;
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        (self.cipher_nonce, self.mask).encode(buf);
+        self.solution.encode(buf);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        let (cipher_nonce, mask, solution) = GuestData::decode(bytes)?;
+        Ok(SudokuAuxInput {
+            cipher_nonce,
+            mask,
+            solution,
+        })
+    }
+}
+
+/// The appendix of a sudoku program's journal.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SudokuAppendix {
+    /// The nonce of the cipher which encrypts the solution.
+    pub cipher_nonce: [u8; 12],
+    /// The puzzle, with zeros for the cells which are not revealed.
+    pub puzzle: SudokuBoard,
+    /// The compact solution, encrypted.
+    pub encrypted_solution: [u8; 36],
+}
+
+impl GuestData for SudokuAppendix {
+    const LEN: usize = 12 + 81 + 36;
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        (self.cipher_nonce, self.puzzle, self.encrypted_solution).encode(buf);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        let (cipher_nonce, puzzle, encrypted_solution) = GuestData::decode(bytes)?;
+        Ok(SudokuAppendix {
+            cipher_nonce,
+            puzzle,
+            encrypted_solution,
+        })
+    }
+}
+
+/// The aux input to a program which proves a batch of `N` sudoku solutions at once.
+///
+/// The batch size is encoded before the cipher nonce as 4 big-endian bytes, and each mask is
+/// followed by its solution.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SudokuBatchAuxInput<const N: usize> {
+    /// The nonce of the cipher which encrypts the solutions.
+    pub cipher_nonce: [u8; 12],
+    /// The mask of each puzzle.
+    pub masks: [SudokuBoard; N],
+    /// The solved boards.
+    pub solutions: Secret<[SudokuBoard; N]>,
+}
+
+impl<const N: usize> GuestData for SudokuBatchAuxInput<N> {
+    const LEN: usize = 4 + 12 + N * (81 + 81);
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        (batch_count::<N>(), self.cipher_nonce).encode(buf);
+        for (mask, solution) in self.masks.iter().zip(self.solutions.expose_secret()) {
+            mask.encode(buf);
+            solution.encode(buf);
+        }
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        let (count, cipher_nonce) = GuestData::decode(&bytes[..4 + 12])?;
+        check_batch_count::<N>(count)?;
+
+        let mut masks = [[0; 81]; N];
+        let mut solutions = Zeroizing::new([[0; 81]; N]);
+        for (i, (mask, solution)) in masks.iter_mut().zip(solutions.iter_mut()).enumerate() {
+            let pair = &bytes[4 + 12 + i * (81 + 81)..];
+            mask.copy_from_slice(&pair[..81]);
+            solution.copy_from_slice(&pair[81..][..81]);
+        }
+        Ok(SudokuBatchAuxInput {
+            cipher_nonce,
+            masks,
+            solutions: Secret::new(*solutions),
+        })
+    }
+}
+
+/// The appendix of the journal of a program which proves a batch of `N` sudoku solutions.
+///
+/// The batch size is encoded first, as 4 big-endian bytes. Decoding fails if it is not `N`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SudokuBatchAppendix<const N: usize> {
+    /// The nonce of the cipher which encrypts the solutions.
+    pub cipher_nonce: [u8; 12],
+    /// The puzzles, with zeros for the cells which are not revealed.
+    pub puzzles: [SudokuBoard; N],
+    /// The compact solutions, encrypted as one continuous keystream.
+    pub encrypted_solutions: [[u8; 36]; N],
+}
+
+impl<const N: usize> GuestData for SudokuBatchAppendix<N> {
+    const LEN: usize = 4 + 12 + N * (81 + 36);
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        (batch_count::<N>(), self.cipher_nonce).encode(buf);
+        (self.puzzles, self.encrypted_solutions).encode(buf);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        let (count, cipher_nonce, (puzzles, encrypted_solutions)) = GuestData::decode(bytes)?;
+        check_batch_count::<N>(count)?;
+        Ok(SudokuBatchAppendix {
+            cipher_nonce,
+            puzzles,
+            encrypted_solutions,
+        })
+    }
+}
+
+fn batch_count<const N: usize>() -> [u8; 4] {
+    (N as u32).to_be_bytes()
+}
+
+fn check_batch_count<const N: usize>(count: [u8; 4]) -> Result<(), anyhow::Error> {
+    let count = u32::from_be_bytes(count);
+    if count as usize != N {
+        bail!("expected a batch of {N} sudoku boards; got {count}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sudoku_batch_roundtrip() {
+        let aux_input = SudokuBatchAuxInput::<2> {
+            cipher_nonce: [1; 12],
+            masks: [[2; 81], [3; 81]],
+            solutions: Secret::new([[4; 81], [5; 81]]),
+        };
+        let bytes = aux_input.to_bytes();
+        assert_eq!(bytes.len(), SudokuBatchAuxInput::<2>::LEN);
+        assert_eq!(bytes[..4], [0, 0, 0, 2]);
+        assert_eq!(bytes[16..][..81], [2; 81]);
+        assert_eq!(bytes[16 + 81..][..81], [4; 81]);
+        assert_eq!(SudokuBatchAuxInput::from_bytes(&bytes).unwrap(), aux_input);
+
+        let appendix = SudokuBatchAppendix::<2> {
+            cipher_nonce: [1; 12],
+            puzzles: [[2; 81], [3; 81]],
+            encrypted_solutions: [[4; 36], [5; 36]],
+        };
+        let bytes = appendix.to_bytes();
+        assert_eq!(SudokuBatchAppendix::from_bytes(&bytes).unwrap(), appendix);
+
+        // A batch of a different size must not decode, even if the length happens to match.
+        let mut bytes = bytes.to_vec();
+        bytes[3] = 3;
+        assert!(SudokuBatchAppendix::<2>::from_bytes(&bytes).is_err());
+    }
+}
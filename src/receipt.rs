@@ -0,0 +1,121 @@
+//! Helpers for producing and verifying RISC0 receipts.
+//!
+//! # Dev mode
+//!
+//! Generating a zk-STARK proof takes minutes, which is too slow for most integration tests.
+//! When this crate is compiled with the `dev-mode` feature and the `RISC0_DEV_MODE` environment
+//! variable is set, proving executes the guest program without proving it, and produces a _fake_
+//! receipt which carries the real journal but no zk-STARK seal.
+//!
+//! Fake receipts prove nothing. They are always rejected by `verify()`, and are only accepted by
+//! `verify_with_opts()` if the crate is compiled with the `dev-mode` feature _and_
+//! [`VerifyOpts::allow_fake_receipts`] is set.
+
+use anyhow::bail;
+use risc0_zkvm::sha::{Digest, Digestible as _};
+use risc0_zkvm::{
+    ExecutorEnv, InnerReceipt, LocalProver, Prover, ProverOpts, Receipt, ReceiptClaim,
+};
+
+use crate::error::VerifyError;
+use crate::program::Program;
+
+/// Options which control how a proof is verified.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VerifyOpts {
+    /// Accept fake receipts produced in dev mode. This has no effect unless the crate is
+    /// compiled with the `dev-mode` feature; otherwise fake receipts are always rejected.
+    ///
+    /// Never set this outside of tests.
+    pub allow_fake_receipts: bool,
+}
+
+/// Build an environment which streams `input` to the guest, and which resolves the guest's
+/// calls to `env::verify` with the given `assumptions`.
+pub(crate) fn executor_env<'a>(
+    input: &'a [u8],
+    assumptions: &[Receipt],
+) -> Result<ExecutorEnv<'a>, anyhow::Error> {
+    let mut builder = ExecutorEnv::builder();
+    for assumption in assumptions {
+        builder.add_assumption(assumption.clone());
+    }
+    builder.stdin(input).build()
+}
+
+/// Prove the execution of the program `P` with the given environment.
+///
+/// If the `dev-mode` feature is enabled and `RISC0_DEV_MODE` is set, this only executes
+/// the guest, and returns a fake receipt.
+pub(crate) fn prove<P: Program>(env: ExecutorEnv<'_>) -> Result<Receipt, anyhow::Error> {
+    #[cfg(feature = "dev-mode")]
+    if risc0_zkvm::is_dev_mode() {
+        let session_info = risc0_zkvm::default_executor().execute(env, P::elf())?;
+        let claim = ReceiptClaim::ok(P::id(), session_info.journal.bytes.clone());
+        let inner = InnerReceipt::Fake(risc0_zkvm::FakeReceipt::new(claim));
+        return Ok(Receipt::new(inner, session_info.journal.bytes));
+    }
+
+    #[cfg(not(feature = "dev-mode"))]
+    if risc0_zkvm::is_dev_mode() {
+        bail!("RISC0_DEV_MODE is set, but zkcp was compiled without the dev-mode feature");
+    }
+
+    // This call takes a while.
+    let prove_info =
+        LocalProver::new("local").prove_with_opts(env, P::elf(), &ProverOpts::fast())?;
+    Ok(prove_info.receipt)
+}
+
+/// Return the image ID of the program which `receipt` claims to have executed. This is
+/// read from the receipt's claim, so it means nothing until the receipt is verified.
+pub(crate) fn claimed_image_id(receipt: &Receipt) -> Result<[u32; 8], anyhow::Error> {
+    let claim = receipt.claim()?;
+    let image_id: Digest = claim.as_value()?.pre.digest();
+    Ok(image_id.into())
+}
+
+/// Check that `receipt` claims to have executed one of the programs in `allowed_ids`, and
+/// return the image ID it claims.
+pub(crate) fn check_allowed_image_id(
+    receipt: &Receipt,
+    allowed_ids: &[[u32; 8]],
+) -> Result<[u32; 8], anyhow::Error> {
+    let image_id = claimed_image_id(receipt)?;
+    if !allowed_ids.contains(&image_id) {
+        bail!(VerifyError::ImageIdNotAllowed { image_id });
+    }
+    Ok(image_id)
+}
+
+/// Verify that `receipt` proves the program `P` executed correctly.
+pub(crate) fn verify<P: Program>(
+    receipt: &Receipt,
+    opts: &VerifyOpts,
+) -> Result<(), anyhow::Error> {
+    verify_image_id(receipt, P::id(), opts)
+}
+
+/// Verify that `receipt` proves the program with the given image ID executed correctly.
+pub(crate) fn verify_image_id(
+    receipt: &Receipt,
+    image_id: [u32; 8],
+    opts: &VerifyOpts,
+) -> Result<(), anyhow::Error> {
+    // RISC0 will accept fake receipts if RISC0_DEV_MODE is set, so we must
+    // reject them ourselves, regardless of the environment.
+    if let InnerReceipt::Fake(fake_receipt) = &receipt.inner {
+        if !cfg!(feature = "dev-mode") || !opts.allow_fake_receipts {
+            bail!(VerifyError::FakeReceipt);
+        }
+
+        let expected_claim = ReceiptClaim::ok(image_id, receipt.journal.bytes.clone());
+        if fake_receipt.claim.digest() != expected_claim.digest() {
+            bail!(VerifyError::FakeReceiptClaimMismatch);
+        }
+        return Ok(());
+    }
+
+    receipt.verify(image_id).map_err(VerifyError::Receipt)?;
+    Ok(())
+}
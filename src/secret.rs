@@ -0,0 +1,49 @@
+use zeroize::Zeroize;
+
+/// A wrapper around secret material, such as a SHA256 preimage.
+///
+/// The inner value is zeroized when dropped, and is redacted from `Debug` output,
+/// so that it cannot leak into logs by accident. Use [`expose_secret`][Self::expose_secret]
+/// to access it.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    pub fn new(secret: T) -> Self {
+        Secret(secret)
+    }
+
+    /// Return a reference to the secret value.
+    pub fn expose_secret(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(secret: T) -> Self {
+        Secret(secret)
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> std::fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_debug_is_redacted() {
+        let secret = Secret::new([0xAB; 32]);
+        assert_eq!(format!("{:?}", secret), "Secret([REDACTED])");
+    }
+}
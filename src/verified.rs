@@ -0,0 +1,37 @@
+use std::ops::Deref;
+
+/// A proof which has been verified.
+///
+/// The only way to obtain a `Verified` proof is to call `into_verified` on a proof, which
+/// runs the full verification procedure. Functions which must only ever handle valid proofs
+/// can accept `Verified<T>` to enforce this at compile time.
+///
+/// `Verified` deliberately does not implement `Deserialize`, nor provide mutable access to
+/// the inner proof, as either would allow the verification guarantee to be bypassed.
+#[derive(Clone, Debug)]
+pub struct Verified<T>(T);
+
+impl<T> Verified<T> {
+    pub(crate) fn new(proof: T) -> Self {
+        Verified(proof)
+    }
+
+    /// Unwrap the verified proof, discarding the guarantee that it is valid.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Verified<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> AsRef<T> for Verified<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
@@ -0,0 +1,246 @@
+//! Tests that tampered and malformed proofs are rejected. These use fake receipts,
+//! so that the Schnorr and journal checks can be exercised quickly. Run with:
+//!
+//! ```not_rust
+//! RISC0_DEV_MODE=1 cargo test --features dev-mode
+//! ```
+#![cfg(feature = "dev-mode")]
+
+use std::io;
+
+use risc0_zkvm::{FakeReceipt, InnerReceipt, Receipt, ReceiptClaim};
+use zkcp::error::VerifyError;
+use zkcp::hashes::PROTOCOL_VERSION;
+use zkcp::program::Program;
+use zkcp::proofs::dlog_secp256k1_sha256::Secp256k1DlogSha256Proof;
+use zkcp::proofs::dlog_secp256k1_sudoku::Secp256k1DlogSudokuProof;
+use zkcp::proofs::sha256_sudoku::Sha256SudokuProgram;
+use zkcp::receipt::VerifyOpts;
+use zkcp::secp::Scalar;
+
+const ALLOW_FAKE: VerifyOpts = VerifyOpts {
+    allow_fake_receipts: true,
+};
+
+fn dlog_sha256_proof() -> Secp256k1DlogSha256Proof {
+    std::env::set_var("RISC0_DEV_MODE", "1");
+    let secret_key = Scalar::reduce_from(&[3u8; 32]);
+    let proof = Secp256k1DlogSha256Proof::new(secret_key).unwrap();
+    proof.verify_with_opts(&ALLOW_FAKE).unwrap();
+    proof
+}
+
+/// Rebuild `proof` around a copy of its receipt, with the journal modified by `tamper`.
+fn tamper_journal(
+    proof: &Secp256k1DlogSha256Proof,
+    tamper: impl FnOnce(&mut Vec<u8>),
+) -> Result<Secp256k1DlogSha256Proof, anyhow::Error> {
+    let mut receipt = proof.receipt().clone();
+    tamper(&mut receipt.journal.bytes);
+    Secp256k1DlogSha256Proof::from_receipt(proof.public_key, proof.public_nonce, receipt)
+}
+
+fn verify_error<T>(result: Result<T, anyhow::Error>) -> VerifyError {
+    match result {
+        Ok(_) => panic!("expected verification to fail"),
+        Err(e) => e.downcast::<VerifyError>().expect("expected a VerifyError"),
+    }
+}
+
+#[test]
+fn test_reject_flipped_journal_byte() {
+    let proof = dlog_sha256_proof();
+
+    // Challenge
+    let tampered = tamper_journal(&proof, |journal| journal[5] ^= 1).unwrap();
+    assert!(matches!(
+        verify_error(tampered.verify_with_opts(&ALLOW_FAKE)),
+        VerifyError::ChallengeMismatch
+    ));
+
+    // Signature
+    let tampered = tamper_journal(&proof, |journal| journal[40] ^= 1).unwrap();
+    assert!(matches!(
+        verify_error(tampered.verify_with_opts(&ALLOW_FAKE)),
+        VerifyError::InvalidSignature
+    ));
+
+    // Appendix
+    let tampered = tamper_journal(&proof, |journal| journal[70] ^= 1).unwrap();
+    assert!(matches!(
+        verify_error(tampered.verify_with_opts(&ALLOW_FAKE)),
+        VerifyError::FakeReceiptClaimMismatch
+    ));
+}
+
+#[test]
+fn test_reject_swapped_public_key_and_nonce() {
+    let mut proof = dlog_sha256_proof();
+    std::mem::swap(&mut proof.public_key, &mut proof.public_nonce);
+    assert!(matches!(
+        verify_error(proof.verify_with_opts(&ALLOW_FAKE)),
+        VerifyError::ChallengeMismatch
+    ));
+}
+
+#[test]
+fn test_reject_receipt_from_different_program() {
+    let proof = dlog_sha256_proof();
+
+    // A dlog-sha256 proof reinterpreted as a dlog-sudoku proof.
+    let bytes = proof.to_vec().unwrap();
+    let err = Secp256k1DlogSudokuProof::from_bytes(&bytes).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    // Same journal, but a receipt claiming a different image ID.
+    let other_id = Sha256SudokuProgram::id();
+    let journal = proof.journal().to_vec();
+    let claim = ReceiptClaim::ok(other_id, journal.clone());
+    let receipt = Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim)), journal);
+    let forged =
+        Secp256k1DlogSha256Proof::from_receipt(proof.public_key, proof.public_nonce, receipt)
+            .unwrap();
+    assert!(matches!(
+        verify_error(forged.verify_with_opts(&ALLOW_FAKE)),
+        VerifyError::ImageIdNotAllowed { image_id } if image_id == other_id
+    ));
+}
+
+#[test]
+fn test_reject_truncated_borsh_data() {
+    let proof = dlog_sha256_proof();
+    let bytes = proof.to_vec().unwrap();
+
+    for len in [0, 1, 32, 33, 65, 66, 100, bytes.len() - 1] {
+        let err = Secp256k1DlogSha256Proof::from_bytes(&bytes[..len]).unwrap_err();
+        assert!(
+            matches!(
+                err.kind(),
+                io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof
+            ),
+            "unexpected error kind for truncation at {len}: {err}"
+        );
+    }
+
+    // Trailing garbage is also rejected.
+    let mut extended = bytes;
+    extended.push(0);
+    assert!(Secp256k1DlogSha256Proof::from_bytes(&extended).is_err());
+}
+
+#[test]
+fn test_reject_invalid_public_key_encoding() {
+    let proof = dlog_sha256_proof();
+    let mut bytes = proof.to_vec().unwrap();
+    bytes[1] = 0x04; // not a valid compressed point prefix
+    let err = Secp256k1DlogSha256Proof::from_bytes(&bytes).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_reject_unknown_format_version() {
+    let proof = dlog_sha256_proof();
+    let mut bytes = proof.to_vec().unwrap();
+    assert_eq!(bytes[0], PROTOCOL_VERSION);
+
+    bytes[0] = PROTOCOL_VERSION + 1;
+    let err = Secp256k1DlogSha256Proof::from_bytes(&bytes).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    // Legacy proofs had no version prefix, and began with a compressed public key.
+    let err = Secp256k1DlogSha256Proof::from_bytes(&bytes[1..]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_reject_challenge_mismatch() {
+    let proof = dlog_sha256_proof();
+    let other = Secp256k1DlogSha256Proof::new(Scalar::reduce_from(&[4u8; 32])).unwrap();
+
+    let tampered = tamper_journal(&proof, |journal| {
+        journal[..32].copy_from_slice(&other.journal()[..32])
+    })
+    .unwrap();
+    assert!(matches!(
+        verify_error(tampered.verify_with_opts(&ALLOW_FAKE)),
+        VerifyError::ChallengeMismatch
+    ));
+}
+
+#[test]
+fn test_reject_zero_and_out_of_range_scalars() {
+    let proof = dlog_sha256_proof();
+
+    // Zero challenge
+    let tampered = tamper_journal(&proof, |journal| journal[..32].fill(0)).unwrap();
+    assert!(matches!(
+        verify_error(tampered.verify_with_opts(&ALLOW_FAKE)),
+        VerifyError::ChallengeMismatch
+    ));
+
+    // Out of range challenge
+    let tampered = tamper_journal(&proof, |journal| journal[..32].fill(0xFF)).unwrap();
+    assert!(matches!(
+        verify_error(tampered.verify_with_opts(&ALLOW_FAKE)),
+        VerifyError::InvalidScalar
+    ));
+
+    // Zero signature
+    let tampered = tamper_journal(&proof, |journal| journal[32..64].fill(0)).unwrap();
+    assert!(matches!(
+        verify_error(tampered.verify_with_opts(&ALLOW_FAKE)),
+        VerifyError::InvalidSignature
+    ));
+
+    // Out of range signature
+    let tampered = tamper_journal(&proof, |journal| journal[32..64].fill(0xFF)).unwrap();
+    assert!(matches!(
+        verify_error(tampered.verify_with_opts(&ALLOW_FAKE)),
+        VerifyError::InvalidScalar
+    ));
+}
+
+#[test]
+fn test_reject_fake_receipt_by_default() {
+    let proof = dlog_sha256_proof();
+    assert!(matches!(
+        verify_error(proof.verify()),
+        VerifyError::FakeReceipt
+    ));
+}
+
+#[test]
+fn test_reject_wrong_journal_length() {
+    let proof = dlog_sha256_proof();
+
+    // Proofs with short journals can never be constructed or deserialized, so their
+    // journal accessors cannot panic.
+    assert!(matches!(
+        verify_error(tamper_journal(&proof, |journal| journal.truncate(40))),
+        VerifyError::JournalLength {
+            actual: 40,
+            expected: 96
+        }
+    ));
+
+    let mut receipt = proof.receipt().clone();
+    receipt.journal.bytes.truncate(40);
+    let mut bytes = vec![PROTOCOL_VERSION];
+    bytes.extend_from_slice(&proof.public_key.serialize());
+    bytes.extend_from_slice(&proof.public_nonce.serialize());
+    bytes.extend_from_slice(&borsh::to_vec(&receipt).unwrap());
+    let err = Secp256k1DlogSha256Proof::from_bytes(&bytes).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = Secp256k1DlogSudokuProof::from_bytes(&bytes).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_verified_proof() {
+    let proof = dlog_sha256_proof();
+    assert!(proof.clone().into_verified().is_err());
+
+    let verified = proof.clone().into_verified_with_opts(&ALLOW_FAKE).unwrap();
+    assert_eq!(verified.public_key, proof.public_key);
+    assert_eq!(verified.hash(), proof.hash());
+}
@@ -0,0 +1,644 @@
+//! Fast integration tests using fake receipts. Run with:
+//!
+//! ```not_rust
+//! RISC0_DEV_MODE=1 cargo test --features dev-mode
+//! ```
+#![cfg(feature = "dev-mode")]
+
+use common::test_fixtures::{MASK, SOLUTION};
+use rand_core::{CryptoRng, RngCore};
+use zkcp::cipher::{Aes128CtrCipher, ChaCha20Cipher, GuestCipher, Sha256KeystreamCipher};
+use zkcp::claim::{ProofClaim, SignedAttestation};
+use zkcp::envelope::{AnyDlogSudokuProof, AnyProof, AnySha256SudokuProof};
+use zkcp::error::VerifyError;
+use zkcp::image_ids;
+use zkcp::proofs::dlog_secp256k1_generic::{verify_batch, verify_batch_with_opts};
+use zkcp::proofs::dlog_secp256k1_sha256::Secp256k1DlogSha256Proof;
+use zkcp::proofs::dlog_secp256k1_sudoku::{
+    Secp256k1DlogSudokuAeadProof, Secp256k1DlogSudokuCipherProof, Secp256k1DlogSudokuProof,
+    Secp256k1DlogSudokuUniqueProof, Secp256k1DlogSudokuV2Proof,
+};
+use zkcp::proofs::dlog_secp256k1_sudoku_batch::Secp256k1DlogSudokuBatchProof;
+use zkcp::proofs::dlog_secp256k1_sudoku_multikey::Secp256k1DlogSudokuMultiKeyProof;
+use zkcp::proofs::sha256_sudoku::{
+    Sha256SudokuAeadProof, Sha256SudokuCipherProof, Sha256SudokuProof, Sha256SudokuV2Proof,
+};
+use zkcp::proofs::sha256_sudoku_composed::Sha256SudokuComposedProof;
+use zkcp::proofs::sudoku_data::SudokuAuxInput;
+use zkcp::receipt::VerifyOpts;
+use zkcp::secp::{Scalar, G};
+use zkcp::secret::Secret;
+use zkcp::sudoku;
+
+const ALLOW_FAKE: VerifyOpts = VerifyOpts {
+    allow_fake_receipts: true,
+};
+
+fn enable_dev_mode() {
+    std::env::set_var("RISC0_DEV_MODE", "1");
+}
+
+/// Fills every request with the same byte, which increments with each request. This is
+/// predictable, and only marked as a `CryptoRng` so it can be passed to hedged provers.
+struct CountingRng(u8);
+
+impl RngCore for CountingRng {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.fill(self.0);
+        self.0 = self.0.wrapping_add(1);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for CountingRng {}
+
+#[test]
+fn test_dlog_secp256k1_sudoku_fake_receipt() {
+    enable_dev_mode();
+    let secret_key = Scalar::reduce_from(&[3u8; 32]);
+
+    let proof = Secp256k1DlogSudokuProof::new(secret_key, &SOLUTION, &MASK).unwrap();
+    assert!(proof.verify().is_err());
+    proof.verify_with_opts(&ALLOW_FAKE).unwrap();
+
+    assert_eq!(
+        proof.puzzle(),
+        sudoku::mask_sudoku_solution(&SOLUTION, &MASK)
+    );
+    assert_eq!(proof.decrypt_solution(secret_key).unwrap(), SOLUTION);
+
+    let decoded = Secp256k1DlogSudokuProof::from_bytes(&proof.to_vec().unwrap()).unwrap();
+    assert!(decoded.verify().is_err());
+    decoded.verify_with_opts(&ALLOW_FAKE).unwrap();
+}
+
+#[test]
+fn test_dlog_secp256k1_sha256_fake_receipt() {
+    enable_dev_mode();
+    let secret_key = Scalar::reduce_from(&[3u8; 32]);
+
+    let proof = Secp256k1DlogSha256Proof::new(secret_key).unwrap();
+    assert!(proof.verify().is_err());
+    proof.verify_with_opts(&ALLOW_FAKE).unwrap();
+    assert_eq!(proof.public_key, secret_key.base_point_mul());
+}
+
+#[test]
+fn test_hedged_nonce_fake_receipts() {
+    enable_dev_mode();
+    let secret_key = Scalar::reduce_from(&[3u8; 32]);
+
+    let deterministic = Secp256k1DlogSha256Proof::prove_custom(secret_key, b"").unwrap();
+    let hedged =
+        Secp256k1DlogSha256Proof::prove_custom_with_rng(secret_key, b"", &mut CountingRng(0))
+            .unwrap();
+    assert_eq!(hedged.public_key, deterministic.public_key);
+    assert_ne!(hedged.public_nonce, deterministic.public_nonce);
+    assert_ne!(hedged.claim_digest(), deterministic.claim_digest());
+    assert!(hedged.verify().is_err());
+    hedged.verify_with_opts(&ALLOW_FAKE).unwrap();
+
+    // Different randomness gives a different nonce, and the same randomness the same nonce.
+    let rehedged =
+        Secp256k1DlogSha256Proof::prove_custom_with_rng(secret_key, b"", &mut CountingRng(1))
+            .unwrap();
+    assert_ne!(rehedged.public_nonce, hedged.public_nonce);
+    let replayed =
+        Secp256k1DlogSha256Proof::prove_custom_with_rng(secret_key, b"", &mut CountingRng(0))
+            .unwrap();
+    assert_eq!(replayed.public_nonce, hedged.public_nonce);
+
+    // Sudoku proofs hedge the chacha nonce as well as the Schnorr nonce.
+    let deterministic = Secp256k1DlogSudokuV2Proof::new(secret_key, &SOLUTION, &MASK).unwrap();
+    let hedged =
+        Secp256k1DlogSudokuV2Proof::new_with_rng(secret_key, &SOLUTION, &MASK, &mut CountingRng(0))
+            .unwrap();
+    assert_ne!(hedged.public_nonce, deterministic.public_nonce);
+    assert_ne!(hedged.appendix()[..12], deterministic.appendix()[..12]);
+    hedged.verify_with_opts(&ALLOW_FAKE).unwrap();
+    assert_eq!(hedged.decrypt_solution(secret_key).unwrap(), SOLUTION);
+}
+
+#[test]
+fn test_sha256_sudoku_fake_receipt() {
+    enable_dev_mode();
+    let preimage = Secret::new([3u8; 32]);
+
+    let proof = Sha256SudokuProof::new(&preimage, &SOLUTION, &MASK).unwrap();
+    assert!(proof.verify().is_err());
+    proof.verify_with_opts(&ALLOW_FAKE).unwrap();
+    assert_eq!(proof.decrypt_solution(&preimage).unwrap(), SOLUTION);
+}
+
+#[test]
+fn test_v2_sudoku_fake_receipts() {
+    enable_dev_mode();
+    let secret_key = Scalar::reduce_from(&[3u8; 32]);
+    let proof = Secp256k1DlogSudokuV2Proof::new(secret_key, &SOLUTION, &MASK).unwrap();
+    proof.verify_with_opts(&ALLOW_FAKE).unwrap();
+    assert_eq!(proof.decrypt_solution(secret_key).unwrap(), SOLUTION);
+
+    // The v1 and v2 programs encrypt the solution under different keys.
+    let v1 = Secp256k1DlogSudokuProof::new(secret_key, &SOLUTION, &MASK).unwrap();
+    assert_ne!(proof.appendix(), v1.appendix());
+
+    let preimage = Secret::new([3u8; 32]);
+    let proof = Sha256SudokuV2Proof::new(&preimage, &SOLUTION, &MASK).unwrap();
+    proof.verify_with_opts(&ALLOW_FAKE).unwrap();
+    assert_eq!(proof.decrypt_solution(&preimage).unwrap(), SOLUTION);
+}
+
+#[test]
+fn test_aead_sudoku_fake_receipts() {
+    enable_dev_mode();
+    let secret_key = Scalar::reduce_from(&[3u8; 32]);
+    let proof = Secp256k1DlogSudokuAeadProof::new(secret_key, &SOLUTION, &MASK).unwrap();
+    proof.verify_with_opts(&ALLOW_FAKE).unwrap();
+    assert_eq!(proof.appendix().len(), 12 + 81 + 36 + 16);
+    assert_eq!(proof.decrypt_solution(secret_key).unwrap(), SOLUTION);
+
+    // Corrupting the ciphertext, tag or puzzle is caught by authentication.
+    for i in [64 + 12, 64 + 12 + 81, 64 + 12 + 81 + 36] {
+        let mut receipt = proof.receipt().clone();
+        receipt.journal.bytes[i + 1] ^= 1;
+        let tampered = Secp256k1DlogSudokuAeadProof::from_receipt(
+            proof.public_key,
+            proof.public_nonce,
+            receipt,
+        )
+        .unwrap();
+        assert!(tampered.decrypt_solution(secret_key).is_err());
+    }
+
+    let preimage = Secret::new([3u8; 32]);
+    let proof = Sha256SudokuAeadProof::new(&preimage, &SOLUTION, &MASK).unwrap();
+    proof.verify_with_opts(&ALLOW_FAKE).unwrap();
+    assert_eq!(proof.decrypt_solution(&preimage).unwrap(), SOLUTION);
+
+    let mut receipt = proof.into_receipt();
+    receipt.journal.bytes[32 + 12 + 81] ^= 1;
+    let tampered = Sha256SudokuAeadProof::from_receipt(receipt).unwrap();
+    assert!(tampered.decrypt_solution(&preimage).is_err());
+}
+
+fn check_cipher<C: GuestCipher>() {
+    let secret_key = Scalar::reduce_from(&[3u8; 32]);
+    let proof = Secp256k1DlogSudokuCipherProof::<C>::new(secret_key, &SOLUTION, &MASK).unwrap();
+    proof.verify_with_opts(&ALLOW_FAKE).unwrap();
+    assert_eq!(proof.appendix().last(), Some(&C::ID));
+    assert_eq!(proof.decrypt_solution(secret_key).unwrap(), SOLUTION);
+
+    let preimage = Secret::new([3u8; 32]);
+    let proof = Sha256SudokuCipherProof::<C>::new(&preimage, &SOLUTION, &MASK).unwrap();
+    proof.verify_with_opts(&ALLOW_FAKE).unwrap();
+    assert_eq!(proof.decrypt_solution(&preimage).unwrap(), SOLUTION);
+}
+
+#[test]
+fn test_cipher_sudoku_fake_receipts() {
+    enable_dev_mode();
+    check_cipher::<ChaCha20Cipher>();
+    check_cipher::<Aes128CtrCipher>();
+    check_cipher::<Sha256KeystreamCipher>();
+
+    // A proof for one cipher cannot be decoded as a proof for another.
+    let secret_key = Scalar::reduce_from(&[3u8; 32]);
+    let proof =
+        Secp256k1DlogSudokuCipherProof::<Aes128CtrCipher>::new(secret_key, &SOLUTION, &MASK)
+            .unwrap();
+    let bytes = proof.to_vec().unwrap();
+    assert!(Secp256k1DlogSudokuCipherProof::<ChaCha20Cipher>::from_bytes(&bytes).is_err());
+    let json = proof.to_json().unwrap();
+    assert!(Secp256k1DlogSudokuCipherProof::<ChaCha20Cipher>::from_json(&json).is_err());
+
+    // Nor can a proof be constructed if its journal commits to another cipher.
+    let (public_key, public_nonce) = (proof.public_key, proof.public_nonce);
+    let mut receipt = proof.into_receipt();
+    *receipt.journal.bytes.last_mut().unwrap() = ChaCha20Cipher::ID;
+    let err = Secp256k1DlogSudokuCipherProof::<Aes128CtrCipher>::from_receipt(
+        public_key,
+        public_nonce,
+        receipt,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<VerifyError>(),
+        Some(VerifyError::CipherMismatch { .. })
+    ));
+
+    let preimage = Secret::new([3u8; 32]);
+    let proof =
+        Sha256SudokuCipherProof::<Aes128CtrCipher>::new(&preimage, &SOLUTION, &MASK).unwrap();
+    let bytes = proof.to_vec().unwrap();
+    assert!(Sha256SudokuCipherProof::<ChaCha20Cipher>::from_bytes(&bytes).is_err());
+}
+
+#[test]
+fn test_sudoku_batch_fake_receipt() {
+    enable_dev_mode();
+    let secret_key = Scalar::reduce_from(&[3u8; 32]);
+    let mut other_mask = MASK;
+    other_mask[..9].fill(0);
+
+    let proof =
+        Secp256k1DlogSudokuBatchProof::<2>::new(secret_key, &[SOLUTION; 2], &[MASK, other_mask])
+            .unwrap();
+    proof.verify_with_opts(&ALLOW_FAKE).unwrap();
+
+    assert_eq!(proof.len(), 2);
+    assert_eq!(
+        proof.puzzles(),
+        vec![
+            sudoku::mask_sudoku_solution(&SOLUTION, &MASK),
+            sudoku::mask_sudoku_solution(&SOLUTION, &other_mask),
+        ]
+    );
+    assert_eq!(proof.puzzle(2), None);
+    assert_eq!(
+        proof.decrypt_solutions(secret_key).unwrap(),
+        vec![SOLUTION; 2]
+    );
+    assert_eq!(proof.decrypt_solution(1, secret_key).unwrap(), SOLUTION);
+    assert!(proof.decrypt_solution(2, secret_key).is_err());
+
+    // Each solution is decrypted on its own, so a corrupted first item does not stop the
+    // second from being decrypted.
+    let mut receipt = proof.receipt().clone();
+    receipt.journal.bytes[64 + 4 + 12 + 2 * 81] ^= 1;
+    let tampered = Secp256k1DlogSudokuBatchProof::<2>::from_receipt(
+        proof.public_key,
+        proof.public_nonce,
+        receipt,
+    )
+    .unwrap();
+    assert!(tampered.decrypt_solutions(secret_key).is_err());
+    assert!(tampered.decrypt_solution(0, secret_key).is_err());
+    assert_eq!(tampered.decrypt_solution(1, secret_key).unwrap(), SOLUTION);
+
+    // The batch size is part of the journal length, so a proof cannot be decoded as a
+    // batch of a different size.
+    let bytes = proof.to_vec().unwrap();
+    assert!(Secp256k1DlogSudokuBatchProof::<2>::from_bytes(&bytes).is_ok());
+    assert!(Secp256k1DlogSudokuBatchProof::<3>::from_bytes(&bytes).is_err());
+}
+
+#[test]
+fn test_sudoku_multikey_fake_receipt() {
+    enable_dev_mode();
+    let secret_keys = [
+        Scalar::reduce_from(&[3u8; 32]),
+        Scalar::reduce_from(&[4u8; 32]),
+    ];
+    let mut other_mask = MASK;
+    other_mask[..9].fill(0);
+
+    let proof = Secp256k1DlogSudokuMultiKeyProof::<2>::new(
+        &secret_keys,
+        &[SOLUTION; 2],
+        &[MASK, other_mask],
+    )
+    .unwrap();
+    proof.verify_with_opts(&ALLOW_FAKE).unwrap();
+    assert!(proof.challenge(2).is_err());
+    assert!(proof.signature(2).is_err());
+
+    assert_eq!(
+        proof.public_keys(),
+        &[
+            secret_keys[0].base_point_mul(),
+            secret_keys[1].base_point_mul()
+        ]
+    );
+    assert_eq!(
+        proof.puzzles(),
+        vec![
+            sudoku::mask_sudoku_solution(&SOLUTION, &MASK),
+            sudoku::mask_sudoku_solution(&SOLUTION, &other_mask),
+        ]
+    );
+    assert_eq!(proof.decrypt_item(0, secret_keys[0]).unwrap(), SOLUTION);
+    assert_eq!(proof.decrypt_item(1, secret_keys[1]).unwrap(), SOLUTION);
+
+    // Each item can only be decrypted with its own key.
+    assert!(proof.decrypt_item(0, secret_keys[1]).is_err());
+    assert!(proof.decrypt_item(1, secret_keys[0]).is_err());
+    assert!(proof.decrypt_item(2, secret_keys[0]).is_err());
+
+    let bytes = proof.to_vec().unwrap();
+    assert!(Secp256k1DlogSudokuMultiKeyProof::<2>::from_bytes(&bytes).is_ok());
+    assert!(Secp256k1DlogSudokuMultiKeyProof::<3>::from_bytes(&bytes).is_err());
+}
+
+#[test]
+fn test_composed_sudoku_fake_receipt() {
+    enable_dev_mode();
+    let secret_key = Scalar::reduce_from(&[3u8; 32]);
+    let preimage = Secret::new(secret_key.serialize());
+
+    let dlog_proof = Secp256k1DlogSha256Proof::new(secret_key).unwrap();
+    let proof = Sha256SudokuComposedProof::new(&dlog_proof, &preimage, &SOLUTION, &MASK).unwrap();
+
+    proof
+        .verify_for_key_with_opts(dlog_proof.public_key, dlog_proof.public_nonce, &ALLOW_FAKE)
+        .unwrap();
+    assert_eq!(proof.hash(), dlog_proof.hash());
+    assert_eq!(proof.decrypt_solution(&preimage).unwrap(), SOLUTION);
+
+    // The Schnorr signature in the journal is bound to the dlog-sha256 proof's key.
+    let other_proof = Secp256k1DlogSha256Proof::new(Scalar::reduce_from(&[4u8; 32])).unwrap();
+    assert!(proof
+        .verify_for_key_with_opts(
+            other_proof.public_key,
+            other_proof.public_nonce,
+            &ALLOW_FAKE
+        )
+        .is_err());
+
+    // The preimage must match the hash of the assumed proof.
+    assert!(Sha256SudokuComposedProof::new(&other_proof, &preimage, &SOLUTION, &MASK).is_err());
+}
+
+#[test]
+fn test_verify_batch_fake_receipts() {
+    enable_dev_mode();
+    let mut proofs: Vec<Secp256k1DlogSha256Proof> = (1..=5u8)
+        .map(|i| Secp256k1DlogSha256Proof::new(Scalar::reduce_from(&[i; 32])).unwrap())
+        .collect();
+    verify_batch_with_opts(&proofs, &ALLOW_FAKE).unwrap();
+
+    // Fake receipts are rejected unless explicitly allowed.
+    assert_eq!(
+        verify_batch(&proofs).unwrap_err().failed_indices(),
+        vec![0, 1, 2, 3, 4]
+    );
+
+    // A proof for the wrong key fails the challenge check, and a corrupted signature fails
+    // the signature equation.
+    proofs[1].public_key = proofs[2].public_key;
+    let mut receipt = proofs[3].receipt().clone();
+    receipt.journal.bytes[40] ^= 1;
+    proofs[3] = Secp256k1DlogSha256Proof::from_receipt(
+        proofs[3].public_key,
+        proofs[3].public_nonce,
+        receipt,
+    )
+    .unwrap();
+    let err = verify_batch_with_opts(&proofs, &ALLOW_FAKE).unwrap_err();
+    assert_eq!(err.failed_indices(), vec![1, 3]);
+
+    // Each failing proof is reported once, even if it also has a fake receipt.
+    assert_eq!(
+        verify_batch(&proofs).unwrap_err().failed_indices(),
+        vec![0, 1, 2, 3, 4]
+    );
+}
+
+#[test]
+fn test_envelope_roundtrip_fake_receipts() {
+    enable_dev_mode();
+    let secret_key = Scalar::reduce_from(&[3u8; 32]);
+
+    let proof = Secp256k1DlogSudokuV2Proof::new(secret_key, &SOLUTION, &MASK).unwrap();
+    let envelope = AnyProof::from(proof.clone()).to_envelope().unwrap();
+    match AnyProof::from_envelope(&envelope).unwrap() {
+        AnyProof::Secp256k1DlogSudoku(AnyDlogSudokuProof::V2(decoded)) => {
+            decoded.verify_with_opts(&ALLOW_FAKE).unwrap();
+            assert_eq!(decoded.claim_digest(), proof.claim_digest());
+        }
+        other => panic!("decoded wrong proof type {}", other.program_name()),
+    }
+
+    let proof = Secp256k1DlogSha256Proof::new(secret_key).unwrap();
+    let decoded = AnyProof::from_envelope(&AnyProof::from(proof).to_envelope().unwrap()).unwrap();
+    assert_eq!(decoded.program_name(), "dlog-secp256k1-sha256");
+    decoded.verify_with_opts(&ALLOW_FAKE).unwrap();
+    assert!(decoded.verify().is_err());
+
+    // Proofs which share a guest program are told apart by the envelope parameter.
+    let proof =
+        Secp256k1DlogSudokuCipherProof::<Aes128CtrCipher>::new(secret_key, &SOLUTION, &MASK)
+            .unwrap();
+    match AnyProof::from_envelope(&AnyProof::from(proof).to_envelope().unwrap()).unwrap() {
+        AnyProof::Secp256k1DlogSudoku(AnyDlogSudokuProof::Aes128Ctr(decoded)) => {
+            decoded.verify_with_opts(&ALLOW_FAKE).unwrap();
+            assert_eq!(decoded.decrypt_solution(secret_key).unwrap(), SOLUTION);
+        }
+        other => panic!("decoded wrong proof type {}", other.program_name()),
+    }
+
+    let proof =
+        Secp256k1DlogSudokuBatchProof::<2>::new(secret_key, &[SOLUTION; 2], &[MASK; 2]).unwrap();
+    match AnyProof::from_envelope(&AnyProof::from(proof).to_envelope().unwrap()).unwrap() {
+        AnyProof::Secp256k1DlogSudokuBatch(decoded) => {
+            assert_eq!(decoded.len(), 2);
+            decoded.verify_with_opts(&ALLOW_FAKE).unwrap();
+            assert_eq!(
+                decoded.decrypt_solutions(secret_key).unwrap(),
+                [SOLUTION; 2]
+            );
+        }
+        other => panic!("decoded wrong proof type {}", other.program_name()),
+    }
+
+    let secret_keys = [secret_key, Scalar::reduce_from(&[4u8; 32])];
+    let proof =
+        Secp256k1DlogSudokuMultiKeyProof::<2>::new(&secret_keys, &[SOLUTION; 2], &[MASK; 2])
+            .unwrap();
+    match AnyProof::from_envelope(&AnyProof::from(proof).to_envelope().unwrap()).unwrap() {
+        AnyProof::Secp256k1DlogSudokuMultiKey(decoded) => {
+            assert_eq!(
+                decoded.public_keys(),
+                [secret_keys[0] * G, secret_keys[1] * G]
+            );
+            decoded.verify_with_opts(&ALLOW_FAKE).unwrap();
+            assert_eq!(decoded.decrypt_item(1, secret_keys[1]).unwrap(), SOLUTION);
+        }
+        other => panic!("decoded wrong proof type {}", other.program_name()),
+    }
+
+    let preimage = Secret::new([3u8; 32]);
+    let proof = Sha256SudokuV2Proof::new(&preimage, &SOLUTION, &MASK).unwrap();
+    match AnyProof::from_envelope(&AnyProof::from(proof).to_envelope().unwrap()).unwrap() {
+        AnyProof::Sha256Sudoku(AnySha256SudokuProof::V2(decoded)) => {
+            decoded.verify_with_opts(&ALLOW_FAKE).unwrap();
+            assert_eq!(decoded.decrypt_solution(&preimage).unwrap(), SOLUTION);
+        }
+        other => panic!("decoded wrong proof type {}", other.program_name()),
+    }
+}
+
+#[test]
+fn test_unique_sudoku_fake_receipt() {
+    enable_dev_mode();
+    let secret_key = Scalar::reduce_from(&[3u8; 32]);
+
+    // Every hidden cell on the diagonal is the only blank in its row, so the solution is unique.
+    let mut unique_mask = [1u8; 81];
+    for i in 0..9 {
+        unique_mask[i * 10] = 0;
+    }
+    let proof = Secp256k1DlogSudokuUniqueProof::new(secret_key, &SOLUTION, &unique_mask).unwrap();
+    proof.verify_with_opts(&ALLOW_FAKE).unwrap();
+    assert_eq!(proof.decrypt_solution(secret_key).unwrap(), SOLUTION);
+
+    // MASK leaves a puzzle with two solutions, so the guest refuses to prove it.
+    assert!(Secp256k1DlogSudokuUniqueProof::new(secret_key, &SOLUTION, &MASK).is_err());
+    assert!(Secp256k1DlogSudokuV2Proof::new(secret_key, &SOLUTION, &MASK).is_ok());
+}
+
+#[test]
+fn test_json_roundtrip_fake_receipts() {
+    enable_dev_mode();
+    let secret_key = Scalar::reduce_from(&[3u8; 32]);
+
+    let proof = Secp256k1DlogSudokuV2Proof::new(secret_key, &SOLUTION, &MASK).unwrap();
+    let proof_json = proof.to_json().unwrap();
+    let decoded = Secp256k1DlogSudokuV2Proof::from_json(&proof_json).unwrap();
+    decoded.verify_with_opts(&ALLOW_FAKE).unwrap();
+    assert_eq!(decoded.public_key, proof.public_key);
+    assert_eq!(decoded.claim_digest(), proof.claim_digest());
+
+    // Proofs for other programs are rejected.
+    assert!(Secp256k1DlogSudokuProof::from_json(&proof_json).is_err());
+
+    // The journal view must match the receipt.
+    let mut value: serde_json::Value = serde_json::from_str(&proof_json).unwrap();
+    value["journal"]["appendix"] = serde_json::Value::String("00".repeat(129));
+    assert!(Secp256k1DlogSudokuV2Proof::from_json(&value.to_string()).is_err());
+
+    let preimage = Secret::new([3u8; 32]);
+    let proof = Sha256SudokuV2Proof::new(&preimage, &SOLUTION, &MASK).unwrap();
+    let decoded = Sha256SudokuV2Proof::from_json(&proof.to_json().unwrap()).unwrap();
+    decoded.verify_with_opts(&ALLOW_FAKE).unwrap();
+    assert_eq!(decoded.hash(), proof.hash());
+}
+
+#[test]
+fn test_detached_claim_fake_receipts() {
+    enable_dev_mode();
+    let secret_key = Scalar::reduce_from(&[3u8; 32]);
+    let proof = Secp256k1DlogSudokuV2Proof::new(secret_key, &SOLUTION, &MASK).unwrap();
+
+    let claim = ProofClaim::from_bytes(&proof.claim().to_vec().unwrap()).unwrap();
+    assert_eq!(claim.digest(), proof.claim_digest());
+    claim
+        .verify_receipt_with_opts(proof.receipt(), &ALLOW_FAKE)
+        .unwrap();
+    assert!(claim.verify_receipt(proof.receipt()).is_err());
+
+    // A receipt for another proof does not prove the claim.
+    let preimage = Secret::new([3u8; 32]);
+    let other = Sha256SudokuV2Proof::new(&preimage, &SOLUTION, &MASK).unwrap();
+    assert_eq!(other.claim().digest(), other.claim_digest());
+    assert!(claim
+        .verify_receipt_with_opts(other.receipt(), &ALLOW_FAKE)
+        .is_err());
+
+    // Neither does a receipt for the same journal from another program.
+    let mut wrong_program = other.claim();
+    wrong_program.image_id = claim.image_id;
+    assert!(wrong_program
+        .verify_receipt_with_opts(other.receipt(), &ALLOW_FAKE)
+        .is_err());
+
+    let signing_key = Scalar::reduce_from(&[9u8; 32]);
+    let attestation = SignedAttestation::sign(claim.clone(), signing_key);
+    attestation.verify(signing_key * G).unwrap();
+    assert_eq!(attestation.claim, claim);
+}
+
+#[test]
+fn test_verify_with_allowed_ids_fake_receipts() {
+    enable_dev_mode();
+    let secret_key = Scalar::reduce_from(&[3u8; 32]);
+    let proof = Secp256k1DlogSudokuV2Proof::new(secret_key, &SOLUTION, &MASK).unwrap();
+
+    let v2_ids = image_ids::image_ids_for("dlog-secp256k1-sudoku-v2");
+    let v1_ids = image_ids::image_ids_for("dlog-secp256k1-sudoku");
+    proof
+        .verify_with_allowed_ids_and_opts(&v2_ids, &ALLOW_FAKE)
+        .unwrap();
+    proof
+        .verify_with_allowed_ids_and_opts(&[v1_ids[0], v2_ids[0]], &ALLOW_FAKE)
+        .unwrap();
+
+    let err = proof
+        .verify_with_allowed_ids_and_opts(&v1_ids, &ALLOW_FAKE)
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<VerifyError>(),
+        Some(VerifyError::ImageIdNotAllowed { .. })
+    ));
+    assert!(err
+        .to_string()
+        .starts_with("proof was produced by program version dlog-secp256k1-sudoku-v2 from zkcp"));
+    assert!(err
+        .to_string()
+        .ends_with("which this verifier does not accept"));
+
+    let preimage = Secret::new([3u8; 32]);
+    let proof = Sha256SudokuV2Proof::new(&preimage, &SOLUTION, &MASK).unwrap();
+    proof
+        .verify_with_allowed_ids_and_opts(
+            &image_ids::image_ids_for("sha256-sudoku-v2"),
+            &ALLOW_FAKE,
+        )
+        .unwrap();
+    assert!(proof
+        .verify_with_allowed_ids_and_opts(&v2_ids, &ALLOW_FAKE)
+        .is_err());
+
+    let secret_keys = [secret_key, Scalar::reduce_from(&[4u8; 32])];
+    let proof =
+        Secp256k1DlogSudokuMultiKeyProof::<2>::new(&secret_keys, &[SOLUTION; 2], &[MASK; 2])
+            .unwrap();
+    proof
+        .verify_with_allowed_ids_and_opts(
+            &image_ids::image_ids_for("dlog-secp256k1-sudoku-multikey"),
+            &ALLOW_FAKE,
+        )
+        .unwrap();
+    let err = proof
+        .verify_with_allowed_ids_and_opts(&v2_ids, &ALLOW_FAKE)
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<VerifyError>(),
+        Some(VerifyError::ImageIdNotAllowed { .. })
+    ));
+}
+
+#[test]
+fn test_typed_aux_input_fake_receipts() {
+    enable_dev_mode();
+    let aux_input = SudokuAuxInput {
+        cipher_nonce: [7; 12],
+        mask: MASK,
+        solution: Secret::new(SOLUTION),
+    };
+
+    let secret_key = Scalar::reduce_from(&[3u8; 32]);
+    let proof = Secp256k1DlogSudokuV2Proof::prove(secret_key, &aux_input).unwrap();
+    proof.verify_with_opts(&ALLOW_FAKE).unwrap();
+    let appendix = proof.appendix_typed().unwrap();
+    assert_eq!(appendix.cipher_nonce, aux_input.cipher_nonce);
+    assert_eq!(appendix.puzzle, proof.puzzle());
+    assert_eq!(proof.decrypt_solution(secret_key).unwrap(), SOLUTION);
+
+    let preimage = Secret::new([3u8; 32]);
+    let proof = Sha256SudokuV2Proof::prove(&preimage, &aux_input).unwrap();
+    proof.verify_with_opts(&ALLOW_FAKE).unwrap();
+    assert_eq!(proof.appendix_typed().unwrap().puzzle, proof.puzzle());
+    assert_eq!(proof.decrypt_solution(&preimage).unwrap(), SOLUTION);
+}
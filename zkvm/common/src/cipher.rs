@@ -0,0 +1,186 @@
+//! Symmetric stream ciphers which guest programs can use to encrypt their outputs.
+//!
+//! Each cipher is identified by a one-byte [`GuestCipher::ID`], which guests commit to
+//! their journal so that the host knows which cipher to decrypt with. Ciphers differ
+//! greatly in their cost in zkVM cycles, so the cheapest cipher for a given payload
+//! can be chosen by executing the same program with each one.
+//!
+//! All ciphers take a 32-byte key and a 12-byte nonce. Keys must never be reused with
+//! the same nonce.
+
+use aes::cipher::{KeyIvInit, StreamCipher, StreamCipherSeek};
+use sha2::{Digest, Sha256};
+
+/// A stream cipher usable inside the zkVM. Applying the keystream twice with the same
+/// key and nonce recovers the plaintext.
+pub trait GuestCipher {
+    /// Identifies the cipher in program inputs and journals.
+    const ID: u8;
+
+    /// XOR the keystream derived from `key` and `nonce` into `data`.
+    fn apply_keystream(key: &[u8; 32], nonce: &[u8; 12], data: &mut [u8]);
+}
+
+/// The ChaCha20 stream cipher, as defined in RFC 8439.
+#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ChaCha20Cipher;
+
+impl GuestCipher for ChaCha20Cipher {
+    const ID: u8 = 0;
+
+    fn apply_keystream(key: &[u8; 32], nonce: &[u8; 12], data: &mut [u8]) {
+        Self::apply_keystream_at(key, nonce, 0, data);
+    }
+}
+
+impl ChaCha20Cipher {
+    /// XOR the keystream derived from `key` and `nonce` into `data`, starting `offset` bytes
+    /// into the keystream. This decrypts one item of a longer ciphertext without touching
+    /// the rest.
+    pub fn apply_keystream_at(key: &[u8; 32], nonce: &[u8; 12], offset: u64, data: &mut [u8]) {
+        let mut cipher = chacha20::ChaCha20::new(key.as_slice().into(), nonce.as_slice().into());
+        cipher.seek(offset);
+        cipher.apply_keystream(data);
+    }
+}
+
+/// AES-128 in counter mode. Only the first 16 bytes of the key are used. The counter
+/// block is the nonce followed by a 32-bit big-endian block counter starting at zero.
+#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Aes128CtrCipher;
+
+impl GuestCipher for Aes128CtrCipher {
+    const ID: u8 = 1;
+
+    fn apply_keystream(key: &[u8; 32], nonce: &[u8; 12], data: &mut [u8]) {
+        let mut iv = [0u8; 16];
+        iv[..12].copy_from_slice(nonce);
+        let mut cipher = ctr::Ctr32BE::<aes::Aes128>::new(key[..16].into(), &iv.into());
+        cipher.apply_keystream(data);
+    }
+}
+
+/// A keystream made of SHA256 hashes, which is cheap in the zkVM thanks to its SHA256
+/// accelerator:
+///
+/// ```not_rust
+/// block_i = SHA256(key || nonce || i)
+/// ```
+///
+/// where `i` is a 32-bit big-endian block counter starting at zero.
+#[derive(Copy, Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Sha256KeystreamCipher;
+
+impl GuestCipher for Sha256KeystreamCipher {
+    const ID: u8 = 2;
+
+    fn apply_keystream(key: &[u8; 32], nonce: &[u8; 12], data: &mut [u8]) {
+        for (i, chunk) in data.chunks_mut(32).enumerate() {
+            let block = Sha256::new()
+                .chain_update(key)
+                .chain_update(nonce)
+                .chain_update((i as u32).to_be_bytes())
+                .finalize();
+            for (byte, key_byte) in chunk.iter_mut().zip(block) {
+                *byte ^= key_byte;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UnknownCipherError(pub u8);
+impl std::fmt::Display for UnknownCipherError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "unknown cipher ID {}", self.0)
+    }
+}
+impl std::error::Error for UnknownCipherError {}
+
+/// Returns the name of the cipher with the given ID, if it is known.
+pub fn cipher_name(id: u8) -> Option<&'static str> {
+    match id {
+        ChaCha20Cipher::ID => Some("chacha20"),
+        Aes128CtrCipher::ID => Some("aes-128-ctr"),
+        Sha256KeystreamCipher::ID => Some("sha256-keystream"),
+        _ => None,
+    }
+}
+
+/// Apply the keystream of the cipher with the given ID, for programs which let the
+/// prover choose a cipher at runtime.
+pub fn apply_keystream_by_id(
+    id: u8,
+    key: &[u8; 32],
+    nonce: &[u8; 12],
+    data: &mut [u8],
+) -> Result<(), UnknownCipherError> {
+    let apply_keystream = match id {
+        ChaCha20Cipher::ID => ChaCha20Cipher::apply_keystream,
+        Aes128CtrCipher::ID => Aes128CtrCipher::apply_keystream,
+        Sha256KeystreamCipher::ID => Sha256KeystreamCipher::apply_keystream,
+        _ => return Err(UnknownCipherError(id)),
+    };
+    apply_keystream(key, nonce, data);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip<C: GuestCipher>() {
+        let key = [7u8; 32];
+        let nonce = [9u8; 12];
+        let plaintext = [42u8; 100];
+
+        let mut data = plaintext;
+        C::apply_keystream(&key, &nonce, &mut data);
+        assert_ne!(data, plaintext);
+
+        let mut other = plaintext;
+        C::apply_keystream(&key, &[10u8; 12], &mut other);
+        assert_ne!(data, other);
+
+        C::apply_keystream(&key, &nonce, &mut data);
+        assert_eq!(data, plaintext);
+    }
+
+    #[test]
+    fn test_cipher_roundtrip() {
+        roundtrip::<ChaCha20Cipher>();
+        roundtrip::<Aes128CtrCipher>();
+        roundtrip::<Sha256KeystreamCipher>();
+    }
+
+    #[test]
+    fn test_chacha20_apply_keystream_at() {
+        let key = [7u8; 32];
+        let nonce = [9u8; 12];
+
+        let mut whole = [42u8; 200];
+        ChaCha20Cipher::apply_keystream(&key, &nonce, &mut whole);
+        for offset in [0, 36, 64, 100] {
+            let mut part = [42u8; 36];
+            ChaCha20Cipher::apply_keystream_at(&key, &nonce, offset as u64, &mut part);
+            assert_eq!(part, whole[offset..][..36]);
+        }
+    }
+
+    #[test]
+    fn test_apply_keystream_by_id() {
+        let key = [7u8; 32];
+        let nonce = [9u8; 12];
+
+        let mut expected = [42u8; 36];
+        Aes128CtrCipher::apply_keystream(&key, &nonce, &mut expected);
+        let mut data = [42u8; 36];
+        apply_keystream_by_id(Aes128CtrCipher::ID, &key, &nonce, &mut data).unwrap();
+        assert_eq!(data, expected);
+
+        assert_eq!(
+            apply_keystream_by_id(3, &key, &nonce, &mut data),
+            Err(UnknownCipherError(3))
+        );
+    }
+}
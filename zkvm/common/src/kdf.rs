@@ -0,0 +1,31 @@
+//! Key derivation for the symmetric ciphers used by guest programs.
+//!
+//! Secret keys and preimages may also be used as Bitcoin keys or payment preimages,
+//! so they should never be used verbatim as symmetric keys. Instead, the cipher key is
+//! derived with a BIP340-style tagged hash:
+//!
+//! ```not_rust
+//! cipher_key = SHA256(SHA256("zkcp/cipher_key") || SHA256("zkcp/cipher_key") || secret)
+//! ```
+//!
+//! This module only defines the hash input, so that the guest can hash it with the
+//! accelerated zkVM SHA256 implementation, and the host with any other.
+
+/// The tag used to derive cipher keys.
+pub const CIPHER_KEY_TAG: &[u8] = b"zkcp/cipher_key";
+
+/// `SHA256(CIPHER_KEY_TAG)`
+pub const CIPHER_KEY_TAG_HASH: [u8; 32] = [
+    0x50, 0xde, 0x28, 0x8a, 0x85, 0xed, 0xf1, 0x69, 0x38, 0xb1, 0x7c, 0x04, 0x8f, 0x57, 0x41, 0xe6,
+    0x4b, 0xe0, 0x2b, 0xa6, 0xd1, 0xd4, 0x51, 0x00, 0x39, 0x35, 0x1c, 0x1c, 0x65, 0xba, 0xdd, 0x23,
+];
+
+/// Returns the SHA256 input from which a cipher key is derived from `secret`. The
+/// output contains `secret` and should be wiped after use.
+pub fn cipher_key_kdf_input(secret: &[u8; 32]) -> [u8; 96] {
+    let mut input = [0u8; 96];
+    input[..32].copy_from_slice(&CIPHER_KEY_TAG_HASH);
+    input[32..64].copy_from_slice(&CIPHER_KEY_TAG_HASH);
+    input[64..].copy_from_slice(secret);
+    input
+}
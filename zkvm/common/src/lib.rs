@@ -1,4 +1,14 @@
+pub mod kdf;
 pub mod sudoku;
 
+#[cfg(feature = "cipher")]
+pub mod cipher;
+
 #[cfg(feature = "secp256k1")]
 pub mod secp256k1;
+
+#[cfg(feature = "solver")]
+pub mod solver;
+
+#[cfg(any(test, feature = "test-fixtures"))]
+pub mod test_fixtures;
@@ -0,0 +1,408 @@
+//! A sudoku solver and puzzle generator.
+//!
+//! The host uses it to generate puzzles and to check the puzzles of sudoku proofs. The
+//! `dlog_secp256k1_sudoku_unique` guest also runs [`has_unique_solution`] inside the zkVM,
+//! where every step of the search costs proving time. The solving functions are re-exported
+//! from [`crate::sudoku`].
+//!
+//! Solving is done with Knuth's [dancing links](https://arxiv.org/abs/cs/0011047) (DLX)
+//! algorithm, which treats sudoku as an exact cover problem. Each of the 729 candidate
+//! placements of a digit in a cell covers four of 324 constraints: the cell is filled,
+//! and its row, column and box each contain the digit.
+
+use rand_core::RngCore;
+
+use crate::sudoku::SudokuBoard;
+
+/// The fewest clues which a sudoku puzzle with a unique solution can have.
+pub const MIN_CLUES: usize = 17;
+
+/// Number of exact cover constraints: cells, row digits, column digits and box digits.
+const CONSTRAINTS: usize = 4 * 81;
+
+/// The header of the list of column headers.
+const ROOT: usize = 0;
+
+/// Sparse exact cover matrix with dancing links. Nodes `1..=CONSTRAINTS` are the column
+/// headers, and every other node is a `1` in the matrix.
+struct Dlx {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    row: Vec<usize>,
+    size: Vec<usize>,
+    covered: Vec<bool>,
+    row_nodes: Vec<usize>,
+}
+
+impl Dlx {
+    /// Build the matrix, adding the candidate rows in the given order. The order of the
+    /// rows decides which solution is found first.
+    fn new(row_order: &[usize]) -> Self {
+        let n = 1 + CONSTRAINTS;
+        let mut dlx = Dlx {
+            left: (0..n).map(|i| (i + n - 1) % n).collect(),
+            right: (0..n).map(|i| (i + 1) % n).collect(),
+            up: (0..n).collect(),
+            down: (0..n).collect(),
+            column: (0..n).collect(),
+            row: vec![usize::MAX; n],
+            size: vec![0; n],
+            covered: vec![false; n],
+            row_nodes: vec![0; 729],
+        };
+        for &row in row_order {
+            dlx.add_row(row);
+        }
+        dlx
+    }
+
+    /// Add the candidate row which places digit `row % 9 + 1` in cell `row / 9`.
+    fn add_row(&mut self, row: usize) {
+        let (cell, digit) = (row / 9, row % 9);
+        let (r, c) = (cell / 9, cell % 9);
+        let b = r / 3 * 3 + c / 3;
+        let constraints = [
+            cell,
+            81 + r * 9 + digit,
+            162 + c * 9 + digit,
+            243 + b * 9 + digit,
+        ];
+
+        let first = self.left.len();
+        self.row_nodes[row] = first;
+        for (k, constraint) in constraints.into_iter().enumerate() {
+            let header = constraint + 1;
+            let node = first + k;
+
+            let above = self.up[header];
+            self.up.push(above);
+            self.down.push(header);
+            self.down[above] = node;
+            self.up[header] = node;
+
+            self.left.push(if k == 0 { first + 3 } else { node - 1 });
+            self.right.push(if k == 3 { first } else { node + 1 });
+            self.column.push(header);
+            self.row.push(row);
+            self.size[header] += 1;
+        }
+    }
+
+    fn cover(&mut self, header: usize) {
+        self.covered[header] = true;
+        self.right[self.left[header]] = self.right[header];
+        self.left[self.right[header]] = self.left[header];
+        let mut i = self.down[header];
+        while i != header {
+            let mut j = self.right[i];
+            while j != i {
+                self.up[self.down[j]] = self.up[j];
+                self.down[self.up[j]] = self.down[j];
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    fn uncover(&mut self, header: usize) {
+        let mut i = self.up[header];
+        while i != header {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                self.up[self.down[j]] = j;
+                self.down[self.up[j]] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+        self.right[self.left[header]] = header;
+        self.left[self.right[header]] = header;
+        self.covered[header] = false;
+    }
+
+    /// Fix the given row as part of the solution. Returns false if it conflicts with a
+    /// row which was already fixed.
+    fn select(&mut self, row: usize) -> bool {
+        let first = self.row_nodes[row];
+        if (first..first + 4).any(|node| self.covered[self.column[node]]) {
+            return false;
+        }
+        for node in first..first + 4 {
+            self.cover(self.column[node]);
+        }
+        true
+    }
+
+    /// Search for solutions until `limit` are found, recording the rows of the first.
+    fn search(
+        &mut self,
+        chosen: &mut Vec<usize>,
+        limit: usize,
+        found: &mut usize,
+        first: &mut Option<Vec<usize>>,
+    ) {
+        if self.right[ROOT] == ROOT {
+            *found += 1;
+            if first.is_none() {
+                *first = Some(chosen.clone());
+            }
+            return;
+        }
+
+        // Branch on the constraint with the fewest candidates.
+        let mut header = self.right[ROOT];
+        let mut j = self.right[header];
+        while j != ROOT {
+            if self.size[j] < self.size[header] {
+                header = j;
+            }
+            j = self.right[j];
+        }
+        if self.size[header] == 0 {
+            return;
+        }
+
+        self.cover(header);
+        let mut r = self.down[header];
+        while r != header && *found < limit {
+            chosen.push(self.row[r]);
+            let mut j = self.right[r];
+            while j != r {
+                self.cover(self.column[j]);
+                j = self.right[j];
+            }
+
+            self.search(chosen, limit, found, first);
+
+            let mut j = self.left[r];
+            while j != r {
+                self.uncover(self.column[j]);
+                j = self.left[j];
+            }
+            chosen.pop();
+            r = self.down[r];
+        }
+        self.uncover(header);
+    }
+}
+
+/// Count the solutions to `puzzle`, stopping at `limit`, and return the count along with
+/// the first solution found. Candidate rows are tried in `row_order`.
+fn search(puzzle: &SudokuBoard, limit: usize, row_order: &[usize]) -> (usize, Option<SudokuBoard>) {
+    if puzzle.iter().any(|&cell| cell > 9) {
+        return (0, None);
+    }
+
+    let mut dlx = Dlx::new(row_order);
+    for (cell, &digit) in puzzle.iter().enumerate() {
+        if digit != 0 && !dlx.select(cell * 9 + digit as usize - 1) {
+            return (0, None);
+        }
+    }
+
+    let mut found = 0;
+    let mut first = None;
+    dlx.search(&mut Vec::with_capacity(81), limit, &mut found, &mut first);
+
+    let solution = first.map(|rows| {
+        let mut solution = *puzzle;
+        for row in rows {
+            solution[row / 9] = (row % 9) as u8 + 1;
+        }
+        solution
+    });
+    (found, solution)
+}
+
+fn natural_order() -> Vec<usize> {
+    (0..729).collect()
+}
+
+/// Solve a sudoku puzzle, in which blank cells are `0`. Returns `None` if the puzzle has
+/// no solution. If it has several, returns one of them.
+pub fn solve(puzzle: &SudokuBoard) -> Option<SudokuBoard> {
+    search(puzzle, 1, &natural_order()).1
+}
+
+/// Count the solutions to a sudoku puzzle, stopping once `limit` solutions are found.
+pub fn count_solutions(puzzle: &SudokuBoard, limit: usize) -> usize {
+    search(puzzle, limit, &natural_order()).0
+}
+
+/// Returns true if the puzzle has exactly one solution.
+pub fn has_unique_solution(puzzle: &SudokuBoard) -> bool {
+    count_solutions(puzzle, 2) == 1
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GenerateError {
+    /// Puzzles must have between [`MIN_CLUES`] and 81 clues.
+    InvalidClueCount(usize),
+    /// No puzzle with a unique solution and the requested number of clues was found.
+    NotFound(usize),
+}
+impl std::fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GenerateError::InvalidClueCount(clues) => {
+                write!(f, "puzzles must have {MIN_CLUES} to 81 clues; got {clues}")
+            }
+            GenerateError::NotFound(clues) => {
+                write!(f, "failed to generate a unique puzzle with {clues} clues")
+            }
+        }
+    }
+}
+impl std::error::Error for GenerateError {}
+
+/// A generated puzzle, along with its unique solution and the mask which turns one into
+/// the other, as taken by the sudoku proof constructors.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GeneratedPuzzle {
+    pub solution: SudokuBoard,
+    pub puzzle: SudokuBoard,
+    pub mask: SudokuBoard,
+}
+
+/// How many random solutions to try removing clues from before giving up.
+const GENERATE_ATTEMPTS: usize = 20;
+
+fn shuffle<T, R: RngCore>(items: &mut [T], rng: &mut R) {
+    for i in (1..items.len()).rev() {
+        let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+/// Generate a random puzzle with exactly `clues` clues and a unique solution.
+///
+/// A random solution is generated first, and then clues are removed in a random order
+/// as long as the solution stays unique. Puzzles with fewer than about 25 clues are rare,
+/// so generating them may fail with [`GenerateError::NotFound`].
+pub fn generate<R: RngCore>(clues: usize, rng: &mut R) -> Result<GeneratedPuzzle, GenerateError> {
+    if !(MIN_CLUES..=81).contains(&clues) {
+        return Err(GenerateError::InvalidClueCount(clues));
+    }
+
+    for _ in 0..GENERATE_ATTEMPTS {
+        let mut row_order = natural_order();
+        shuffle(&mut row_order, rng);
+        let solution = search(&[0; 81], 1, &row_order)
+            .1
+            .expect("empty board is solvable");
+
+        let mut cells: Vec<usize> = (0..81).collect();
+        shuffle(&mut cells, rng);
+
+        let mut puzzle = solution;
+        let mut remaining = 81;
+        for cell in cells {
+            if remaining == clues {
+                break;
+            }
+            puzzle[cell] = 0;
+            if has_unique_solution(&puzzle) {
+                remaining -= 1;
+            } else {
+                puzzle[cell] = solution[cell];
+            }
+        }
+
+        if remaining == clues {
+            let mask = puzzle.map(|cell| (cell != 0) as u8);
+            return Ok(GeneratedPuzzle {
+                solution,
+                puzzle,
+                mask,
+            });
+        }
+    }
+    Err(GenerateError::NotFound(clues))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::{is_valid_sudoku_solution, mask_sudoku_solution, solves_sudoku_puzzle};
+    use crate::test_fixtures::{MASK, SOLUTION};
+
+    /// A xorshift generator, which is plenty for tests.
+    struct TestRng(u64);
+
+    impl RngCore for TestRng {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+        fn next_u64(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            rand_core::impls::fill_bytes_via_next(self, dest)
+        }
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_solve() {
+        let puzzle = mask_sudoku_solution(&SOLUTION, &MASK);
+        let solution = solve(&puzzle).unwrap();
+        assert!(is_valid_sudoku_solution(&solution));
+        assert!(solves_sudoku_puzzle(&solution, &puzzle));
+
+        // This puzzle was made by masking a solution at random, and it has two solutions.
+        assert_eq!(count_solutions(&puzzle, 10), 2);
+        assert!(!has_unique_solution(&puzzle));
+
+        // The empty board has many solutions.
+        assert_eq!(count_solutions(&[0; 81], 5), 5);
+        assert!(is_valid_sudoku_solution(&solve(&[0; 81]).unwrap()));
+
+        // Conflicting clues have no solution.
+        let mut conflicting = puzzle;
+        conflicting[8] = 6;
+        assert_eq!(solve(&conflicting), None);
+        assert_eq!(count_solutions(&conflicting, 2), 0);
+
+        let mut invalid = puzzle;
+        invalid[8] = 10;
+        assert_eq!(solve(&invalid), None);
+    }
+
+    #[test]
+    fn test_generate() {
+        let mut rng = TestRng(0x2545_f491_4f6c_dd1d);
+        let generated = generate(30, &mut rng).unwrap();
+
+        assert!(is_valid_sudoku_solution(&generated.solution));
+        assert_eq!(
+            generated.puzzle.iter().filter(|&&cell| cell != 0).count(),
+            30
+        );
+        assert_eq!(
+            mask_sudoku_solution(&generated.solution, &generated.mask),
+            generated.puzzle
+        );
+        assert!(has_unique_solution(&generated.puzzle));
+        assert_eq!(solve(&generated.puzzle), Some(generated.solution));
+
+        assert_eq!(
+            generate(16, &mut rng),
+            Err(GenerateError::InvalidClueCount(16))
+        );
+        assert_eq!(
+            generate(82, &mut rng),
+            Err(GenerateError::InvalidClueCount(82))
+        );
+    }
+}
@@ -1,3 +1,6 @@
+#[cfg(feature = "solver")]
+pub use crate::solver::{count_solutions, has_unique_solution, solve};
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct DecompressionError;
 impl std::fmt::Display for DecompressionError {
@@ -160,9 +163,160 @@ pub fn solves_sudoku_puzzle(solution: &SudokuBoard, puzzle: &SudokuBoard) -> boo
     solution.iter().zip(puzzle).all(|(&s, &p)| p == 0 || s == p)
 }
 
+/// A sudoku board which can be parsed from and rendered to the common text interchange
+/// formats. Blank cells are stored as `0`.
+///
+/// Parsing with [`FromStr`][std::str::FromStr] accepts any of these formats:
+///
+/// - The 81-character line format, with `.` or `0` for blanks.
+/// - The multi-line grid format, with `|`, `-` and `+` as box separators.
+/// - The SDK format: nine lines of nine characters, with `#` comment lines.
+/// - A single line of the SDM format, which is the line format with `0` for blanks.
+///
+/// Whitespace and box separators are ignored, so every format reduces to 81 cells read
+/// left to right, top to bottom. Use [`Sudoku::parse_sdm`] to parse a whole SDM file.
+///
+/// [`Display`][std::fmt::Display] renders the line format with `.` for blanks. The
+/// alternate flag (`{:#}`) renders the grid format instead.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Sudoku(pub SudokuBoard);
+
+/// The error returned when parsing a [`Sudoku`] fails.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseSudokuError {
+    /// The text contains a character which is neither a cell nor a separator.
+    InvalidCharacter(char),
+    /// The text does not contain exactly 81 cells.
+    CellCount(usize),
+}
+impl std::fmt::Display for ParseSudokuError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseSudokuError::InvalidCharacter(c) => {
+                write!(f, "invalid character {c:?} in sudoku board")
+            }
+            ParseSudokuError::CellCount(n) => {
+                write!(f, "sudoku board must have 81 cells; found {n}")
+            }
+        }
+    }
+}
+impl std::error::Error for ParseSudokuError {}
+
+impl std::str::FromStr for Sudoku {
+    type Err = ParseSudokuError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut board = [0u8; 81];
+        let mut n = 0;
+        for line in s.lines().filter(|line| !line.trim_start().starts_with('#')) {
+            for c in line.chars() {
+                let cell = match c {
+                    '.' => 0,
+                    '0'..='9' => c as u8 - b'0',
+                    '|' | '-' | '+' => continue,
+                    c if c.is_whitespace() => continue,
+                    c => return Err(ParseSudokuError::InvalidCharacter(c)),
+                };
+                if n < 81 {
+                    board[n] = cell;
+                }
+                n += 1;
+            }
+        }
+        if n != 81 {
+            return Err(ParseSudokuError::CellCount(n));
+        }
+        Ok(Sudoku(board))
+    }
+}
+
+impl Sudoku {
+    /// Render a cell, with `blank` for empty cells.
+    fn cell_char(&self, i: usize, blank: char) -> char {
+        match self.0[i] {
+            0 => blank,
+            d => char::from_digit(d as u32, 10).unwrap_or('?'),
+        }
+    }
+
+    /// Render the board in the SDK format: nine lines of nine characters, with `.` for
+    /// blanks.
+    pub fn to_sdk(&self) -> String {
+        let mut out = String::with_capacity(90);
+        for row in 0..9 {
+            out.extend((0..9).map(|col| self.cell_char(row * 9 + col, '.')));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Parse every board in an SDM file, which contains one board per line in the line
+    /// format. Blank lines are skipped.
+    pub fn parse_sdm(s: &str) -> Result<Vec<Sudoku>, ParseSudokuError> {
+        s.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    /// Render `boards` as an SDM file: one board per line, with `0` for blanks.
+    pub fn to_sdm(boards: &[Sudoku]) -> String {
+        let mut out = String::with_capacity(boards.len() * 82);
+        for board in boards {
+            out.extend((0..81).map(|i| board.cell_char(i, '0')));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl std::fmt::Display for Sudoku {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if !f.alternate() {
+            for i in 0..81 {
+                write!(f, "{}", self.cell_char(i, '.'))?;
+            }
+            return Ok(());
+        }
+
+        for row in 0..9 {
+            if row == 3 || row == 6 {
+                f.write_str("------+-------+------\n")?;
+            }
+            for col in 0..9 {
+                if col == 3 || col == 6 {
+                    f.write_str(" |")?;
+                }
+                if col > 0 {
+                    f.write_str(" ")?;
+                }
+                write!(f, "{}", self.cell_char(row * 9 + col, '.'))?;
+            }
+            if row < 8 {
+                f.write_str("\n")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<SudokuBoard> for Sudoku {
+    fn from(board: SudokuBoard) -> Self {
+        Sudoku(board)
+    }
+}
+
+impl From<Sudoku> for SudokuBoard {
+    fn from(sudoku: Sudoku) -> Self {
+        sudoku.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_fixtures::SOLUTION;
 
     #[test]
     fn test_is_valid_sudoku_solution() {
@@ -294,4 +448,45 @@ mod tests {
         assert_eq!(decompress_board(&compress_board(&board)), Ok(board));
         assert!(decompress_board(&[0xFF; 36]).is_err());
     }
+
+    #[test]
+    fn test_sudoku_text_formats() {
+        let solution = Sudoku(SOLUTION);
+        let mut puzzle = solution;
+        for i in 0..9 {
+            puzzle.0[i * 10] = 0;
+        }
+
+        let line = puzzle.to_string();
+        assert_eq!(line.len(), 81);
+        assert!(line.starts_with(".14389257"));
+        assert_eq!(line.parse::<Sudoku>(), Ok(puzzle));
+        assert_eq!(line.replace('.', "0").parse::<Sudoku>(), Ok(puzzle));
+
+        let grid = format!("{:#}", puzzle);
+        assert_eq!(grid.lines().next(), Some(". 1 4 | 3 8 9 | 2 5 7"));
+        assert_eq!(grid.lines().nth(3), Some("------+-------+------"));
+        assert_eq!(grid.lines().count(), 11);
+        assert_eq!(grid.parse::<Sudoku>(), Ok(puzzle));
+
+        let sdk = format!("#A someone\n{}", puzzle.to_sdk());
+        assert_eq!(sdk.lines().nth(1), Some(".14389257"));
+        assert_eq!(sdk.parse::<Sudoku>(), Ok(puzzle));
+
+        let sdm = Sudoku::to_sdm(&[puzzle, solution]);
+        assert_eq!(Sudoku::parse_sdm(&sdm), Ok(vec![puzzle, solution]));
+
+        assert_eq!(
+            "x".repeat(81).parse::<Sudoku>(),
+            Err(ParseSudokuError::InvalidCharacter('x'))
+        );
+        assert_eq!(
+            line[..80].parse::<Sudoku>(),
+            Err(ParseSudokuError::CellCount(80))
+        );
+        assert_eq!(
+            format!("{line}1").parse::<Sudoku>(),
+            Err(ParseSudokuError::CellCount(82))
+        );
+    }
 }
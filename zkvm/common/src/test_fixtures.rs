@@ -0,0 +1,33 @@
+//! Sudoku boards shared by the tests of this crate, and of crates which enable the
+//! `test-fixtures` feature.
+use crate::sudoku::SudokuBoard;
+
+/// A valid sudoku solution.
+pub const SOLUTION: SudokuBoard = [
+    6, 1, 4, /**/ 3, 8, 9, /**/ 2, 5, 7, //
+    5, 8, 3, /**/ 6, 7, 2, /**/ 4, 1, 9, //
+    9, 7, 2, /**/ 5, 4, 1, /**/ 8, 6, 3, //
+    /***********************************/
+    1, 3, 9, /**/ 8, 5, 4, /**/ 6, 7, 2, //
+    2, 5, 8, /**/ 1, 6, 7, /**/ 9, 3, 4, //
+    7, 4, 6, /**/ 2, 9, 3, /**/ 5, 8, 1, //
+    /***********************************/
+    8, 2, 7, /**/ 9, 1, 5, /**/ 3, 4, 6, //
+    4, 9, 5, /**/ 7, 3, 6, /**/ 1, 2, 8, //
+    3, 6, 1, /**/ 4, 2, 8, /**/ 7, 9, 5, //
+];
+
+/// A mask of [`SOLUTION`] which leaves 44 clues.
+pub const MASK: SudokuBoard = [
+    1, 1, 1, /**/ 1, 1, 1, /**/ 1, 1, 0, //
+    1, 0, 0, /**/ 1, 0, 0, /**/ 1, 0, 0, //
+    0, 0, 0, /**/ 1, 0, 0, /**/ 0, 1, 1, //
+    /***********************************/
+    1, 1, 0, /**/ 1, 0, 0, /**/ 1, 1, 0, //
+    1, 0, 1, /**/ 1, 1, 0, /**/ 1, 0, 1, //
+    0, 1, 0, /**/ 1, 0, 1, /**/ 1, 0, 1, //
+    /***********************************/
+    0, 1, 0, /**/ 1, 0, 0, /**/ 1, 0, 0, //
+    1, 0, 1, /**/ 1, 0, 1, /**/ 1, 0, 0, //
+    1, 1, 0, /**/ 1, 1, 0, /**/ 1, 0, 1, //
+];
@@ -0,0 +1,50 @@
+use common::{kdf, secp256k1, sudoku};
+
+use chacha20poly1305::{aead::AeadInPlace, ChaCha20Poly1305, KeyInit};
+use risc0_zkvm::guest::env;
+use risc0_zkvm::guest::sha;
+
+use risc0_zkvm::guest::sha::Sha256;
+
+fn main() {
+    let mut secret_key = [0u8; 32];
+    let mut secret_nonce = [0u8; 32];
+    let mut challenge = [0u8; 32];
+    let mut chacha_nonce = [0u8; 12];
+    let mut sudoku_puzzle_mask = [0u8; 81];
+    let mut sudoku_solution = [0u8; 81];
+
+    env::read_slice(&mut secret_key);
+    env::read_slice(&mut secret_nonce);
+    env::read_slice(&mut challenge);
+    env::read_slice(&mut chacha_nonce);
+    env::read_slice(&mut sudoku_puzzle_mask);
+    env::read_slice(&mut sudoku_solution);
+
+    let sig = secp256k1::schnorr_signature(secret_key, secret_nonce, challenge);
+
+    assert!(sudoku::is_valid_sudoku_solution(&sudoku_solution));
+    let sudoku_puzzle_bytes = sudoku::mask_sudoku_solution(&sudoku_solution, &sudoku_puzzle_mask);
+
+    // Never use the secret key itself as the cipher key.
+    let chacha_key = sha::Impl::hash_bytes(&kdf::cipher_key_kdf_input(&secret_key));
+
+    let mut compact_solution = sudoku::compress_board(&sudoku_solution);
+    let cipher = ChaCha20Poly1305::new(chacha_key.as_bytes().into());
+
+    // The puzzle is authenticated as associated data, binding the ciphertext to it.
+    let tag = cipher
+        .encrypt_in_place_detached(
+            &chacha_nonce.into(),
+            &sudoku_puzzle_bytes,
+            &mut compact_solution,
+        )
+        .expect("sudoku solution is well within the chacha20poly1305 length limit");
+
+    env::commit_slice(&challenge);
+    env::commit_slice(&sig);
+    env::commit_slice(&chacha_nonce);
+    env::commit_slice(&sudoku_puzzle_bytes);
+    env::commit_slice(&compact_solution); // encrypted with chacha20poly1305
+    env::commit_slice(&tag);
+}
@@ -0,0 +1,56 @@
+use common::{kdf, secp256k1, sudoku};
+
+use chacha20::{
+    cipher::{KeyIvInit, StreamCipher},
+    ChaCha20,
+};
+use risc0_zkvm::guest::env;
+use risc0_zkvm::guest::sha;
+
+use risc0_zkvm::guest::sha::Sha256;
+
+fn main() {
+    let mut secret_key = [0u8; 32];
+    let mut secret_nonce = [0u8; 32];
+    let mut challenge = [0u8; 32];
+    let mut count = [0u8; 4];
+    let mut chacha_nonce = [0u8; 12];
+
+    env::read_slice(&mut secret_key);
+    env::read_slice(&mut secret_nonce);
+    env::read_slice(&mut challenge);
+    env::read_slice(&mut count);
+    env::read_slice(&mut chacha_nonce);
+
+    let n = u32::from_be_bytes(count) as usize;
+    let mut sudoku_puzzles = Vec::with_capacity(n * 81);
+    let mut compact_solutions = Vec::with_capacity(n * 36);
+    for _ in 0..n {
+        let mut sudoku_puzzle_mask = [0u8; 81];
+        let mut sudoku_solution = [0u8; 81];
+        env::read_slice(&mut sudoku_puzzle_mask);
+        env::read_slice(&mut sudoku_solution);
+
+        assert!(sudoku::is_valid_sudoku_solution(&sudoku_solution));
+        sudoku_puzzles.extend_from_slice(&sudoku::mask_sudoku_solution(
+            &sudoku_solution,
+            &sudoku_puzzle_mask,
+        ));
+        compact_solutions.extend_from_slice(&sudoku::compress_board(&sudoku_solution));
+    }
+
+    let sig = secp256k1::schnorr_signature(secret_key, secret_nonce, challenge);
+
+    // Never use the secret key itself as the cipher key. All solutions are encrypted
+    // as one continuous keystream, so the nonce is never reused.
+    let chacha_key = sha::Impl::hash_bytes(&kdf::cipher_key_kdf_input(&secret_key));
+    let mut cipher = ChaCha20::new(chacha_key.as_bytes().into(), &chacha_nonce.into());
+    cipher.apply_keystream(&mut compact_solutions);
+
+    env::commit_slice(&challenge);
+    env::commit_slice(&sig);
+    env::commit_slice(&count);
+    env::commit_slice(&chacha_nonce);
+    env::commit_slice(&sudoku_puzzles);
+    env::commit_slice(&compact_solutions); // encrypted with chacha20
+}
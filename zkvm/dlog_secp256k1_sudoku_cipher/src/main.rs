@@ -0,0 +1,49 @@
+use common::{cipher, kdf, secp256k1, sudoku};
+
+use risc0_zkvm::guest::env;
+use risc0_zkvm::guest::sha;
+
+use risc0_zkvm::guest::sha::Sha256;
+
+fn main() {
+    let mut secret_key = [0u8; 32];
+    let mut secret_nonce = [0u8; 32];
+    let mut challenge = [0u8; 32];
+    let mut cipher_nonce = [0u8; 12];
+    let mut sudoku_puzzle_mask = [0u8; 81];
+    let mut sudoku_solution = [0u8; 81];
+    let mut cipher_id = [0u8; 1];
+
+    env::read_slice(&mut secret_key);
+    env::read_slice(&mut secret_nonce);
+    env::read_slice(&mut challenge);
+    env::read_slice(&mut cipher_nonce);
+    env::read_slice(&mut sudoku_puzzle_mask);
+    env::read_slice(&mut sudoku_solution);
+    env::read_slice(&mut cipher_id);
+
+    let sig = secp256k1::schnorr_signature(secret_key, secret_nonce, challenge);
+
+    assert!(sudoku::is_valid_sudoku_solution(&sudoku_solution));
+    let sudoku_puzzle_bytes = sudoku::mask_sudoku_solution(&sudoku_solution, &sudoku_puzzle_mask);
+
+    // Never use the secret key itself as the cipher key.
+    let cipher_key = sha::Impl::hash_bytes(&kdf::cipher_key_kdf_input(&secret_key));
+    let cipher_key = <&[u8; 32]>::try_from(cipher_key.as_bytes()).unwrap();
+
+    let mut compact_solution = sudoku::compress_board(&sudoku_solution);
+    cipher::apply_keystream_by_id(
+        cipher_id[0],
+        cipher_key,
+        &cipher_nonce,
+        &mut compact_solution,
+    )
+    .unwrap();
+
+    env::commit_slice(&challenge);
+    env::commit_slice(&sig);
+    env::commit_slice(&cipher_nonce);
+    env::commit_slice(&sudoku_puzzle_bytes);
+    env::commit_slice(&compact_solution); // encrypted with the chosen cipher
+    env::commit_slice(&cipher_id);
+}
@@ -0,0 +1,57 @@
+use common::{kdf, secp256k1, sudoku};
+
+use chacha20::{
+    cipher::{KeyIvInit, StreamCipher},
+    ChaCha20,
+};
+use risc0_zkvm::guest::env;
+use risc0_zkvm::guest::sha;
+
+use risc0_zkvm::guest::sha::Sha256;
+
+fn main() {
+    let mut count = [0u8; 4];
+    env::read_slice(&mut count);
+    let n = u32::from_be_bytes(count) as usize;
+
+    let mut secret_keys = Vec::with_capacity(n);
+    for _ in 0..n {
+        let mut secret_key = [0u8; 32];
+        let mut secret_nonce = [0u8; 32];
+        let mut challenge = [0u8; 32];
+
+        env::read_slice(&mut secret_key);
+        env::read_slice(&mut secret_nonce);
+        env::read_slice(&mut challenge);
+
+        let sig = secp256k1::schnorr_signature(secret_key, secret_nonce, challenge);
+        env::commit_slice(&challenge);
+        env::commit_slice(&sig);
+
+        secret_keys.push(secret_key);
+    }
+
+    // Each item is encrypted under a key derived from its own secret key.
+    for secret_key in &secret_keys {
+        let mut chacha_nonce = [0u8; 12];
+        let mut sudoku_puzzle_mask = [0u8; 81];
+        let mut sudoku_solution = [0u8; 81];
+
+        env::read_slice(&mut chacha_nonce);
+        env::read_slice(&mut sudoku_puzzle_mask);
+        env::read_slice(&mut sudoku_solution);
+
+        assert!(sudoku::is_valid_sudoku_solution(&sudoku_solution));
+        let sudoku_puzzle_bytes =
+            sudoku::mask_sudoku_solution(&sudoku_solution, &sudoku_puzzle_mask);
+
+        let chacha_key = sha::Impl::hash_bytes(&kdf::cipher_key_kdf_input(secret_key));
+        let mut compact_solution = sudoku::compress_board(&sudoku_solution);
+        let mut cipher = ChaCha20::new(chacha_key.as_bytes().into(), &chacha_nonce.into());
+        cipher.apply_keystream(&mut compact_solution);
+
+        env::commit_slice(&chacha_nonce);
+        env::commit_slice(&sudoku_puzzle_bytes);
+        env::commit_slice(&compact_solution); // encrypted with chacha20
+    }
+}
@@ -0,0 +1,53 @@
+use common::{kdf, sudoku};
+
+use chacha20::{
+    cipher::{KeyIvInit, StreamCipher},
+    ChaCha20,
+};
+use risc0_zkvm::guest::env;
+use risc0_zkvm::guest::sha;
+use risc0_zkvm::sha::Digest;
+
+use risc0_zkvm::guest::sha::Sha256;
+
+fn main() {
+    let mut preimage = [0u8; 32];
+    let mut dlog_image_id = [0u8; 32];
+    let mut dlog_signature = [0u8; 64];
+    let mut chacha_nonce = [0u8; 12];
+    let mut sudoku_puzzle_mask = [0u8; 81];
+    let mut sudoku_solution = [0u8; 81];
+
+    env::read_slice(&mut preimage);
+    env::read_slice(&mut dlog_image_id);
+    env::read_slice(&mut dlog_signature);
+    env::read_slice(&mut chacha_nonce);
+    env::read_slice(&mut sudoku_puzzle_mask);
+    env::read_slice(&mut sudoku_solution);
+
+    let digest = sha::Impl::hash_bytes(&preimage);
+
+    // Assume a dlog-sha256 receipt whose journal binds this hash to a Schnorr signature.
+    // The host supplies the receipt, so the Schnorr signature is never recomputed here.
+    let mut dlog_journal = [0u8; 64 + 32];
+    dlog_journal[..64].copy_from_slice(&dlog_signature);
+    dlog_journal[64..].copy_from_slice(digest.as_bytes());
+    env::verify(Digest::from(dlog_image_id), &dlog_journal).unwrap();
+
+    assert!(sudoku::is_valid_sudoku_solution(&sudoku_solution));
+    let sudoku_puzzle_bytes = sudoku::mask_sudoku_solution(&sudoku_solution, &sudoku_puzzle_mask);
+
+    // Never use the preimage itself as the cipher key.
+    let chacha_key = sha::Impl::hash_bytes(&kdf::cipher_key_kdf_input(&preimage));
+
+    let mut compact_solution = sudoku::compress_board(&sudoku_solution);
+    let mut cipher = ChaCha20::new(chacha_key.as_bytes().into(), &chacha_nonce.into());
+    cipher.apply_keystream(&mut compact_solution);
+
+    env::commit_slice(digest.as_bytes());
+    env::commit_slice(&dlog_image_id);
+    env::commit_slice(&dlog_signature);
+    env::commit_slice(&chacha_nonce);
+    env::commit_slice(&sudoku_puzzle_bytes);
+    env::commit_slice(&compact_solution);
+}